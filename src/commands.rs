@@ -1,64 +1,346 @@
-use crate::config::Config;
-use crate::markdown::{Task, TodoFile};
-use std::collections::HashMap;
+use crate::config::{AutoArchive, BlockedDone, Config};
+use crate::daily;
+use crate::dates;
+use crate::deps;
+use crate::done_log;
+use crate::export::{self, ExportFormat};
+use crate::graph::{self, GraphFormat};
+use crate::html::{self, RenderFormat};
+use crate::import::{self, ImportFormat};
+use crate::lock;
+use crate::markdown::{Section, Task, TodoFile, CREATED, ID, SCHEDULED};
+use crate::picker;
+use crate::state::State;
+use crate::stats::{self, GroupBy};
+use crate::vault;
+use crate::watch;
+use chrono::{NaiveDate, Utc};
+use regex::Regex;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
+use std::io::{self, BufRead, Write};
 
-pub fn list(section_filter: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
-    let config = Config::load()?;
-    let todo_path = config.todo_path()?;
-    let todo = TodoFile::load(&todo_path)?;
+pub fn list(config: &Config, section_filter: Option<String>, ready: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let files = vault::todo_files(config)?;
+    let open_ids = deps::open_ids(files.iter().map(|(_, todo)| todo));
+    let ready_on = if ready { Some(config.today()?) } else { None };
+
+    for (prefix, todo) in &files {
+        print_sections(todo, prefix, section_filter.as_deref(), &open_ids, ready_on);
+    }
 
+    Ok(())
+}
+
+/// Prints tasks by section, marking those with open ⛔ blockers. With
+/// `ready_on`, only tasks ready on that day are shown and empty sections
+/// are skipped.
+fn print_sections(
+    todo: &TodoFile,
+    prefix: &str,
+    section_filter: Option<&str>,
+    open_ids: &HashSet<String>,
+    ready_on: Option<NaiveDate>,
+) {
     let numbered = todo.numbered_tasks();
 
     for section in &todo.sections {
-        if let Some(ref filter) = section_filter {
+        if let Some(filter) = section_filter {
             if !section.name.eq_ignore_ascii_case(filter) {
                 continue;
             }
         }
 
-        println!("## {}", section.name);
+        let tasks: Vec<&(usize, &Task)> = numbered
+            .get(&section.name)
+            .map(|tasks| {
+                tasks
+                    .iter()
+                    .filter(|(_, task)| ready_on.is_none_or(|day| deps::is_ready(task, open_ids, day)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if ready_on.is_some() && tasks.is_empty() {
+            continue;
+        }
 
-        if let Some(tasks) = numbered.get(&section.name) {
-            for (num, task) in tasks {
-                let status = if task.done { "[x]" } else { "[ ]" };
-                let done_marker = match &task.done_date {
-                    Some(date) => format!(" ✅ {}", date),
-                    None => String::new(),
-                };
-                println!("  {}: {} {}{}", num, status, task.text, done_marker);
-            }
+        println!("## {}{}", prefix, section.name);
+
+        for (num, task) in tasks {
+            let status = if task.done { "[x]" } else { "[ ]" };
+            let done_marker = match &task.done_date {
+                Some(date) => format!(" ✅ {}", date),
+                None => String::new(),
+            };
+            let blockers = deps::open_blockers(task, open_ids);
+            let blocked_marker = if task.done || blockers.is_empty() {
+                String::new()
+            } else {
+                format!("  (blocked by {})", blockers.join(", "))
+            };
+            println!("  {}: {} {}{}{}", num, status, task.text, done_marker, blocked_marker);
         }
 
         println!();
     }
+}
+
+pub fn add(config: &Config, section: String, text: String) -> Result<(), Box<dyn std::error::Error>> {
+    let (todo_path, section) = vault::resolve_ref(config, &section)?;
+    let _lock = lock::todo(config)?;
+    let mut todo = TodoFile::load(&todo_path)?;
+
+    add_task(config, &mut todo, &section, &text)?;
+    todo.save(&todo_path)?;
+
+    println!("Added to {}: {}", section, text);
 
     Ok(())
 }
 
-pub fn add(section: String, text: String) -> Result<(), Box<dyn std::error::Error>> {
-    let config = Config::load()?;
-    let todo_path = config.todo_path()?;
-    let mut todo = TodoFile::load(&todo_path)?;
+/// Appends a new task to `section`, creating the section if needed.
+/// Returns its section and task indices; the caller saves `todo`.
+pub(crate) fn add_task(
+    config: &Config,
+    todo: &mut TodoFile,
+    section: &str,
+    text: &str,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let section_idx = todo.get_or_create_section(section);
+    let task = new_task(config, text)?;
+    todo.sections[section_idx].tasks.push(task);
+    Ok((section_idx, todo.sections[section_idx].tasks.len() - 1))
+}
 
-    let section_idx = todo.get_or_create_section(&section);
-    let task = Task {
-        text: text.clone(),
+/// Builds an open task, stamping ➕ today when `stamp_created` is set.
+pub(crate) fn new_task(config: &Config, text: &str) -> Result<Task, Box<dyn std::error::Error>> {
+    let mut task = Task {
+        text: text.to_string(),
         done: false,
         done_date: None,
     };
+    if config.stamp_created {
+        task.set_field(CREATED, &config.today_str()?);
+    }
+    Ok(task)
+}
 
-    todo.sections[section_idx].tasks.push(task);
-    todo.save(&todo_path)?;
+pub fn add_daily(
+    config: &Config,
+    heading: Option<String>,
+    text: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (note_path, _) = daily::ensure_note(config, config.today()?)?;
+    let heading = heading.unwrap_or_else(|| config.daily_heading.clone());
 
-    println!("Added to {}: {}", section, text);
+    // The `daily` done-log layout writes to the same notes under the lock.
+    let _lock = lock::todo(config)?;
+    let note = fs::read_to_string(&note_path)?;
+    let task = new_task(config, &text)?;
+    fs::write(&note_path, daily::insert_tasks(&note, &heading, &[task]))?;
+
+    println!("Added to {} ({}): {}", heading, note_path.display(), text);
+
+    Ok(())
+}
+
+pub fn today(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let (note_path, created) = daily::ensure_note(config, config.today()?)?;
+
+    if created {
+        println!("Created daily note at {}", note_path.display());
+    } else {
+        println!("Daily note: {}", note_path.display());
+    }
+    println!();
+
+    let note = TodoFile::load(&note_path)?;
+    print_sections(&note, "", None, &HashSet::new(), None);
+
+    Ok(())
+}
+
+pub fn carry(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let today = config.today()?;
+    let (prev_date, prev_path) = daily::previous_note(config, today)?
+        .ok_or("No previous daily note found")?;
+    let (note_path, _) = daily::ensure_note(config, today)?;
+
+    let _lock = lock::todo(config)?;
+    let mut prev = TodoFile::load(&prev_path)?;
+    let mut note = fs::read_to_string(&note_path)?;
+    let mut carried = 0;
+
+    for section in &mut prev.sections {
+        let (open, done): (Vec<Task>, Vec<Task>) =
+            section.tasks.drain(..).partition(|t| !t.done);
+        section.tasks = done;
+
+        if open.is_empty() {
+            continue;
+        }
+
+        for task in &open {
+            println!("Carried: {} ({} § {})", task.text, prev_date, section.name);
+        }
+        carried += open.len();
+        note = daily::insert_tasks(&note, &section.name, &open);
+    }
+
+    if carried == 0 {
+        println!("No unfinished tasks in {}", prev_path.display());
+        return Ok(());
+    }
+
+    fs::write(&note_path, note)?;
+    prev.save(&prev_path)?;
+
+    Ok(())
+}
+
+pub fn capture(config: &Config, section: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let stdin = io::stdin();
+    capture_from(config, &mut stdin.lock(), section)
+}
+
+/// Adds every task line read from `input` to the inbox (or `section`).
+pub fn capture_from(
+    config: &Config,
+    input: &mut dyn BufRead,
+    section: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut content = String::new();
+    input.read_to_string(&mut content)?;
+
+    let section = section.unwrap_or_else(|| config.inbox_section.clone());
+    let tasks = parse_capture(config, &content)?;
+    if tasks.is_empty() {
+        println!("Nothing to capture");
+        return Ok(());
+    }
+
+    let todo_path = config.todo_path()?;
+    let _lock = lock::todo(config)?;
+    let mut todo = TodoFile::load(&todo_path)?;
+    let section_idx = todo.get_or_create_section(&section);
+    for task in tasks {
+        println!("Captured to {}: {}", section, task.text);
+        todo.sections[section_idx].tasks.push(task);
+    }
+    todo.save(&todo_path)?;
 
     Ok(())
 }
 
-pub fn done(task_ref: String) -> Result<(), Box<dyn std::error::Error>> {
-    let config = Config::load()?;
+/// Ingests `*.txt` / `*.md` files from the drop directory into the inbox (or
+/// `section`), then deletes them or moves them to `drop_archive_dir`.
+pub fn capture_drop_dir(
+    config: &Config,
+    dir: Option<String>,
+    section: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = dir
+        .or_else(|| config.drop_dir.clone())
+        .ok_or("No drop directory given. Pass --drop-dir <DIR> or set drop_dir")?;
+    let dir = Config::expand_path(&dir)?;
+    let section = section.unwrap_or_else(|| config.inbox_section.clone());
+
+    let mut files: Vec<_> = fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            let hidden = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            let ext_ok = path
+                .extension()
+                .is_some_and(|ext| ext == "txt" || ext == "md");
+            path.is_file() && !hidden && ext_ok
+        })
+        .collect();
+    files.sort();
+
+    if files.is_empty() {
+        println!("No files to capture in {}", dir.display());
+        return Ok(());
+    }
+
     let todo_path = config.todo_path()?;
+    let _lock = lock::todo(config)?;
+    let mut todo = TodoFile::load(&todo_path)?;
+    let section_idx = todo.get_or_create_section(&section);
+
+    for file in &files {
+        let content = fs::read_to_string(file)?;
+        for task in parse_capture(config, &content)? {
+            println!("Captured to {}: {} ({})", section, task.text, file.display());
+            todo.sections[section_idx].tasks.push(task);
+        }
+    }
+
+    todo.save(&todo_path)?;
+
+    let archive_dir = config
+        .drop_archive_dir
+        .as_deref()
+        .map(Config::expand_path)
+        .transpose()?;
+    for file in &files {
+        match (&archive_dir, file.file_name()) {
+            (Some(archive_dir), Some(name)) => {
+                fs::create_dir_all(archive_dir)?;
+                fs::rename(file, archive_dir.join(name))?;
+            }
+            _ => fs::remove_file(file)?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Turns pasted text into tasks: Markdown checklist items keep their state,
+/// other list items and plain lines become open tasks. Headings and blank
+/// lines are skipped; a line starting with a `#tag` is a task.
+fn parse_capture(config: &Config, content: &str) -> Result<Vec<Task>, Box<dyn std::error::Error>> {
+    let bullet_regex = Regex::new(r"^(?:[-*+]|\d+[.)])\s+(?:\[([ xX])\]\s+)?(.*)$").unwrap();
+    let heading_regex = Regex::new(r"^#+(\s|$)").unwrap();
+    let mut tasks = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || heading_regex.is_match(line) {
+            continue;
+        }
+
+        if let Some(task) = Task::from_line(line) {
+            tasks.push(task);
+            continue;
+        }
+
+        let (text, done) = match bullet_regex.captures(line) {
+            Some(caps) => (
+                caps[2].trim().to_string(),
+                caps.get(1).is_some_and(|m| m.as_str() != " "),
+            ),
+            None => (line.to_string(), false),
+        };
+        if text.is_empty() {
+            continue;
+        }
+
+        let mut task = new_task(config, &text)?;
+        if done {
+            task.done = true;
+            task.done_date = Some(config.today_str()?);
+        }
+        tasks.push(task);
+    }
+
+    Ok(tasks)
+}
+
+pub fn done(config: &Config, task_ref: String, force: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let (todo_path, task_ref) = vault::resolve_ref(config, &task_ref)?;
+    let _lock = lock::todo(config)?;
     let mut todo = TodoFile::load(&todo_path)?;
 
     let (section_name, task_num) = parse_task_ref(&task_ref)?;
@@ -71,24 +353,133 @@ pub fn done(task_ref: String) -> Result<(), Box<dyn std::error::Error>> {
         return Err(format!("Task {} not found in section '{}'", task_num, section_name).into());
     }
 
-    let task = &mut todo.sections[section_idx].tasks[task_idx];
-    task.done = true;
-    task.done_date = Some(config.today_str()?);
+    let blockers = check_blockers(config, &todo.sections[section_idx].tasks[task_idx], &section_name, task_num, force)?;
+    if !blockers.is_empty() {
+        println!("⚠ Completing a task blocked by open task(s): {}", blockers.join(", "));
+    }
 
-    let task_text = task.text.clone();
+    let today = config.today_str()?;
+    let task_text = todo.sections[section_idx].tasks[task_idx].text.clone();
+    let archive_now = mark_done(config, &mut todo, section_idx, task_idx)?;
     todo.save(&todo_path)?;
 
     println!(
         "Marked as done: {} ({}:{})",
         task_text, section_name, task_num
     );
+    if archive_now {
+        println!("Archived: {} -> {} § {}", task_text, done_log::file_name(config, &today)?, today);
+    }
 
     Ok(())
 }
 
-pub fn undo(task_ref: String) -> Result<(), Box<dyn std::error::Error>> {
-    let config = Config::load()?;
-    let todo_path = config.todo_path()?;
+/// Open ⛔ blockers of a task about to be completed. Errors when
+/// `blocked_done` is `refuse` and `force` is not set.
+pub(crate) fn check_blockers(
+    config: &Config,
+    task: &Task,
+    section_name: &str,
+    task_num: usize,
+    force: bool,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let open_ids = deps::open_ids(vault::todo_files(config)?.iter().map(|(_, todo)| todo));
+    let blockers = deps::open_blockers(task, &open_ids);
+    if !blockers.is_empty() && config.blocked_done == BlockedDone::Refuse && !force {
+        return Err(format!(
+            "Task {} in section '{}' is blocked by open task(s): {}. Use --force to complete it anyway",
+            task_num,
+            section_name,
+            blockers.join(", ")
+        )
+        .into());
+    }
+    Ok(blockers)
+}
+
+/// Marks a task done today and, with `auto_archive = "on_done"`, moves it
+/// straight to the done log. Returns whether it was archived; the caller
+/// saves `todo`.
+pub(crate) fn mark_done(
+    config: &Config,
+    todo: &mut TodoFile,
+    section_idx: usize,
+    task_idx: usize,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let today = config.today_str()?;
+    let task = &mut todo.sections[section_idx].tasks[task_idx];
+    task.done = true;
+    task.done_date = Some(today.clone());
+
+    if config.auto_archive != AutoArchive::OnDone {
+        return Ok(false);
+    }
+    let section = todo.sections[section_idx].name.clone();
+    let task = todo.sections[section_idx].tasks.remove(task_idx);
+    done_log::record(config, vec![(today, section, task)])?;
+    Ok(true)
+}
+
+/// Makes `task_ref` wait on `blocker_ref`, giving the blocker a 🆔 if it
+/// has none. The two tasks may live in different vault files.
+pub fn depend(config: &Config, task_ref: String, blocker_ref: String) -> Result<(), Box<dyn std::error::Error>> {
+    let (task_path, task_ref) = vault::resolve_ref(config, &task_ref)?;
+    let (blocker_path, blocker_ref) = vault::resolve_ref(config, &blocker_ref)?;
+    let (task_section, task_num) = parse_task_ref(&task_ref)?;
+    let (blocker_section, blocker_num) = parse_task_ref(&blocker_ref)?;
+
+    let _lock = lock::todo(config)?;
+    let mut task_file = TodoFile::load(&task_path)?;
+    let mut blocker_file = if blocker_path == task_path {
+        None
+    } else {
+        Some(TodoFile::load(&blocker_path)?)
+    };
+
+    let locate = |todo: &TodoFile, section: &str, num: usize| -> Result<(usize, usize), Box<dyn std::error::Error>> {
+        let section_idx = todo
+            .find_section(section)
+            .ok_or(format!("Section '{}' not found", section))?;
+        if num == 0 || num > todo.sections[section_idx].tasks.len() {
+            return Err(format!("Task {} not found in section '{}'", num, section).into());
+        }
+        Ok((section_idx, num - 1))
+    };
+    let (ts, ti) = locate(&task_file, &task_section, task_num)?;
+    let (bs, bi) = locate(blocker_file.as_ref().unwrap_or(&task_file), &blocker_section, blocker_num)?;
+    if blocker_file.is_none() && (ts, ti) == (bs, bi) {
+        return Err("A task cannot depend on itself".into());
+    }
+
+    let taken = deps::all_ids(vault::todo_files(config)?.iter().map(|(_, todo)| todo));
+    let blocker = &mut blocker_file.as_mut().unwrap_or(&mut task_file).sections[bs].tasks[bi];
+    let blocker_text = blocker.text.clone();
+    let id = match blocker.field(ID) {
+        Some(id) => id,
+        None => {
+            let id = deps::new_id(&blocker.text, &taken);
+            blocker.set_field(ID, &id);
+            id
+        }
+    };
+
+    let task = &mut task_file.sections[ts].tasks[ti];
+    let task_text = task.text.clone();
+    deps::add_blocker(task, &id);
+
+    task_file.save(&task_path)?;
+    if let Some(blocker_file) = &blocker_file {
+        blocker_file.save(&blocker_path)?;
+    }
+
+    println!("{} now depends on {} (🆔 {})", task_text, blocker_text, id);
+
+    Ok(())
+}
+
+pub fn undo(config: &Config, task_ref: String) -> Result<(), Box<dyn std::error::Error>> {
+    let (todo_path, task_ref) = vault::resolve_ref(config, &task_ref)?;
+    let _lock = lock::todo(config)?;
     let mut todo = TodoFile::load(&todo_path)?;
 
     let (section_name, task_num) = parse_task_ref(&task_ref)?;
@@ -101,11 +492,8 @@ pub fn undo(task_ref: String) -> Result<(), Box<dyn std::error::Error>> {
         return Err(format!("Task {} not found in section '{}'", task_num, section_name).into());
     }
 
-    let task = &mut todo.sections[section_idx].tasks[task_idx];
-    task.done = false;
-    task.done_date = None;
-
-    let task_text = task.text.clone();
+    reopen_task(&mut todo, section_idx, task_idx);
+    let task_text = todo.sections[section_idx].tasks[task_idx].text.clone();
     todo.save(&todo_path)?;
 
     println!(
@@ -116,9 +504,16 @@ pub fn undo(task_ref: String) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-pub fn move_task(task_ref: String, dest: String) -> Result<(), Box<dyn std::error::Error>> {
-    let config = Config::load()?;
-    let todo_path = config.todo_path()?;
+/// Marks a task open again and clears its completion date.
+pub(crate) fn reopen_task(todo: &mut TodoFile, section_idx: usize, task_idx: usize) {
+    let task = &mut todo.sections[section_idx].tasks[task_idx];
+    task.done = false;
+    task.done_date = None;
+}
+
+pub fn move_task(config: &Config, task_ref: String, dest: String) -> Result<(), Box<dyn std::error::Error>> {
+    let (todo_path, task_ref) = vault::resolve_ref(config, &task_ref)?;
+    let _lock = lock::todo(config)?;
     let mut todo = TodoFile::load(&todo_path)?;
 
     let (section_name, task_nums) = parse_task_ref_multi(&task_ref)?;
@@ -126,28 +521,13 @@ pub fn move_task(task_ref: String, dest: String) -> Result<(), Box<dyn std::erro
         .find_section(&section_name)
         .ok_or(format!("Section '{}' not found", section_name))?;
 
-    let mut tasks_to_move = Vec::new();
-    for task_num in task_nums.iter().rev() {
-        let task_idx = task_num - 1;
-        if task_idx >= todo.sections[section_idx].tasks.len() {
-            return Err(
-                format!("Task {} not found in section '{}'", task_num, section_name).into(),
-            );
-        }
-        let task = todo.sections[section_idx].tasks.remove(task_idx);
-        tasks_to_move.push((task_num, task));
-    }
-
-    tasks_to_move.reverse();
-
-    let dest_idx = todo.get_or_create_section(&dest);
+    let moved = move_tasks(&mut todo, section_idx, &task_nums, &dest)?;
 
-    for (task_num, task) in &tasks_to_move {
+    for (task_num, task) in &moved {
         println!(
             "Moved: {} ({}:{} -> {})",
             task.text, section_name, task_num, dest
         );
-        todo.sections[dest_idx].tasks.push(task.clone());
     }
 
     todo.save(&todo_path)?;
@@ -155,11 +535,25 @@ pub fn move_task(task_ref: String, dest: String) -> Result<(), Box<dyn std::erro
     Ok(())
 }
 
-pub fn archive(task_ref: String) -> Result<(), Box<dyn std::error::Error>> {
-    let config = Config::load()?;
-    let todo_path = config.todo_path()?;
-    let done_path = config.done_path()?;
+/// Moves the given 1-based tasks of a section to the end of `dest`,
+/// creating it if needed. Returns the moved tasks with their former
+/// numbers; the caller saves `todo`.
+pub(crate) fn move_tasks(
+    todo: &mut TodoFile,
+    section_idx: usize,
+    task_nums: &[usize],
+    dest: &str,
+) -> Result<Vec<(usize, Task)>, Box<dyn std::error::Error>> {
+    let moved = remove_tasks(todo, section_idx, task_nums)?;
+    let dest_idx = todo.get_or_create_section(dest);
+    todo.sections[dest_idx].tasks.extend(moved.iter().map(|(_, task)| task.clone()));
+    Ok(moved)
+}
+
+pub fn archive(config: &Config, task_ref: String) -> Result<(), Box<dyn std::error::Error>> {
+    let (todo_path, task_ref) = vault::resolve_ref(config, &task_ref)?;
 
+    let _lock = lock::todo(config)?;
     let mut todo = TodoFile::load(&todo_path)?;
 
     let (section_name, spec) = task_ref
@@ -170,6 +564,40 @@ pub fn archive(task_ref: String) -> Result<(), Box<dyn std::error::Error>> {
         .find_section(section_name)
         .ok_or(format!("Section '{}' not found", section_name))?;
 
+    let archived = archive_tasks(config, &mut todo, section_idx, spec)?;
+    if archived.is_empty() {
+        println!("No completed tasks to archive in section '{}'", section_name);
+        return Ok(());
+    }
+
+    for (idx, task) in &archived {
+        let done_date = task.done_date.as_deref().unwrap_or("unknown");
+        println!(
+            "Archived: {} ({}:{} -> {} § {} / {})",
+            task.text,
+            section_name,
+            idx + 1,
+            done_log::file_name(config, done_date)?,
+            done_date,
+            section_name
+        );
+    }
+
+    todo.save(&todo_path)?;
+
+    Ok(())
+}
+
+/// Moves the completed tasks picked by `spec` (`all` or task numbers) from
+/// a section to the done log. Returns them with their former 0-based
+/// positions, last first; the caller saves `todo`.
+pub(crate) fn archive_tasks(
+    config: &Config,
+    todo: &mut TodoFile,
+    section_idx: usize,
+    spec: &str,
+) -> Result<Vec<(usize, Task)>, Box<dyn std::error::Error>> {
+    let section_name = todo.sections[section_idx].name.clone();
     let tasks_to_archive: Vec<(usize, Task)> = if spec == "all" {
         todo.sections[section_idx]
             .tasks
@@ -201,60 +629,547 @@ pub fn archive(task_ref: String) -> Result<(), Box<dyn std::error::Error>> {
     };
 
     if tasks_to_archive.is_empty() {
-        println!("No completed tasks to archive in section '{}'", section_name);
+        return Ok(tasks_to_archive);
+    }
+
+    let entries = tasks_to_archive
+        .iter()
+        .rev()
+        .map(|(_, task)| {
+            let done_date = task.done_date.as_deref().unwrap_or("unknown");
+            (done_date.to_string(), section_name.clone(), task.clone())
+        })
+        .collect();
+    done_log::record(config, entries)?;
+
+    for (idx, _) in &tasks_to_archive {
+        todo.sections[section_idx].tasks.remove(*idx);
+    }
+
+    Ok(tasks_to_archive)
+}
+
+pub fn rollover(config: &Config, force: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let today = config.today_str()?;
+    let state_path = config.state_path()?;
+    let mut state = State::load(&state_path)?;
+
+    if !force && state.last_rollover.as_deref() == Some(today.as_str()) {
+        println!("Already rolled over today ({})", today);
         return Ok(());
     }
 
-    let done_content = load_done_file(&done_path)?;
-    let updated_done = append_to_done(done_content, section_name, &tasks_to_archive)?;
-    save_done_file(&updated_done, &done_path)?;
+    let rollover = &config.rollover;
+    let todo_path = config.todo_path()?;
+    let lock = lock::todo(config)?;
+    let mut todo = TodoFile::load(&todo_path)?;
 
-    for (idx, task) in &tasks_to_archive {
-        let done_date = task.done_date.as_deref().unwrap_or("unknown");
-        println!(
-            "Archived: {} ({}:{} -> done_list.md § {} / {})",
-            task.text,
-            section_name,
-            idx + 1,
-            done_date,
-            section_name
-        );
+    let source_idx = todo
+        .find_section(&rollover.source)
+        .ok_or(format!("Section '{}' not found", rollover.source))?;
+    let source_name = todo.sections[source_idx].name.clone();
+
+    let (completed, mut open): (Vec<Task>, Vec<Task>) = todo.sections[source_idx]
+        .tasks
+        .drain(..)
+        .partition(|t| t.done);
+
+    let mut kept = Vec::new();
+    if rollover.archive_done && !completed.is_empty() {
+        let mut entries = Vec::new();
+        for task in &completed {
+            let done_date = task.done_date.as_deref().unwrap_or("unknown");
+            println!(
+                "Archived: {} ({} -> {} § {} / {})",
+                task.text,
+                source_name,
+                done_log::file_name(config, done_date)?,
+                done_date,
+                source_name
+            );
+            entries.push((done_date.to_string(), source_name.clone(), task.clone()));
+        }
+        done_log::record(config, entries)?;
+    } else {
+        kept = completed;
     }
 
-    for (idx, _) in &tasks_to_archive {
-        todo.sections[section_idx].tasks.remove(*idx);
+    if rollover.count_carried {
+        for task in &mut open {
+            let count = task
+                .inline_field("carried")
+                .and_then(|n| n.parse::<u32>().ok())
+                .unwrap_or(0)
+                + 1;
+            task.set_inline_field("carried", &count.to_string());
+        }
+    }
+
+    match &rollover.destination {
+        Some(dest) => {
+            todo.sections[source_idx].tasks = kept;
+            let dest_idx = todo.get_or_create_section(dest);
+            for task in open {
+                println!("Rolled over: {} ({} -> {})", task.text, source_name, dest);
+                todo.sections[dest_idx].tasks.push(task);
+            }
+        }
+        None => {
+            for task in &open {
+                println!("Carried: {} ({})", task.text, source_name);
+            }
+            open.extend(kept);
+            todo.sections[source_idx].tasks = open;
+        }
     }
 
     todo.save(&todo_path)?;
+    drop(lock);
+
+    if config.auto_archive == AutoArchive::OnRollover {
+        archive_completed(config, None)?;
+    }
+
+    state.last_rollover = Some(today);
+    state.save(&state_path)?;
 
     Ok(())
 }
 
-pub fn delete(task_ref: String) -> Result<(), Box<dyn std::error::Error>> {
-    let config = Config::load()?;
-    let todo_path = config.todo_path()?;
+pub fn snooze(
+    config: &Config,
+    task_ref: String,
+    until: String,
+    dest: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (todo_path, task_ref) = vault::resolve_ref(config, &task_ref)?;
+    let _lock = lock::todo(config)?;
     let mut todo = TodoFile::load(&todo_path)?;
 
-    let (section_name, task_nums) = parse_task_ref_multi(&task_ref)?;
+    let (section_name, task_num) = parse_task_ref(&task_ref)?;
     let section_idx = todo
         .find_section(&section_name)
         .ok_or(format!("Section '{}' not found", section_name))?;
 
-    let mut tasks_to_delete = Vec::new();
-    for task_num in task_nums.iter().rev() {
-        let task_idx = task_num - 1;
-        if task_idx >= todo.sections[section_idx].tasks.len() {
-            return Err(
-                format!("Task {} not found in section '{}'", task_num, section_name).into(),
-            );
+    if task_num == 0 || task_num > todo.sections[section_idx].tasks.len() {
+        return Err(format!("Task {} not found in section '{}'", task_num, section_name).into());
+    }
+    let task_idx = task_num - 1;
+
+    let until = dates::parse_date(&until, config.today()?)?;
+    let dest = dest.unwrap_or_else(|| config.snooze_section.clone());
+
+    let mut task = todo.sections[section_idx].tasks.remove(task_idx);
+    task.set_field(SCHEDULED, &until.format("%Y-%m-%d").to_string());
+    if task.inline_field("snoozed_from").is_none() {
+        let origin = todo.sections[section_idx].name.clone();
+        task.set_inline_field("snoozed_from", &origin);
+    }
+
+    println!(
+        "Snoozed until {}: {} ({}:{} -> {})",
+        until, task.text, section_name, task_num, dest
+    );
+
+    let dest_idx = todo.get_or_create_section(&dest);
+    todo.sections[dest_idx].tasks.push(task);
+    todo.save(&todo_path)?;
+
+    Ok(())
+}
+
+pub fn wake(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    if wake_due(config)? == 0 {
+        println!("No snoozed tasks are due");
+    }
+    Ok(())
+}
+
+/// Moves snoozed tasks whose ⏳ date has arrived back to the section recorded
+/// in their `snoozed_from` field, in every vault file. Returns how many tasks
+/// woke up.
+pub fn wake_due(config: &Config) -> Result<usize, Box<dyn std::error::Error>> {
+    let _lock = lock::todo(config)?;
+    let today = config.today()?;
+
+    let mut count = 0;
+    for (prefix, todo_path) in vault::todo_paths(config)? {
+        let mut todo = TodoFile::load(&todo_path)?;
+
+        let mut woken = Vec::new();
+        for section in &mut todo.sections {
+            let mut idx = 0;
+            while idx < section.tasks.len() {
+                let task = &section.tasks[idx];
+                let due = task.date_field(SCHEDULED).is_some_and(|date| date <= today);
+                match task.inline_field("snoozed_from") {
+                    Some(origin) if due => {
+                        let mut task = section.tasks.remove(idx);
+                        task.remove_inline_field("snoozed_from");
+                        woken.push((section.name.clone(), origin, task));
+                    }
+                    _ => idx += 1,
+                }
+            }
+        }
+
+        if woken.is_empty() {
+            continue;
+        }
+
+        count += woken.len();
+        for (from, origin, task) in woken {
+            println!("Woke: {} ({}{} -> {})", task.text, prefix, from, origin);
+            let dest_idx = todo.get_or_create_section(&origin);
+            todo.sections[dest_idx].tasks.push(task);
+        }
+
+        todo.save(&todo_path)?;
+    }
+
+    Ok(count)
+}
+
+pub fn wait(
+    config: &Config,
+    task_ref: String,
+    person: String,
+    follow_up: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (todo_path, task_ref) = vault::resolve_ref(config, &task_ref)?;
+    let _lock = lock::todo(config)?;
+    let mut todo = TodoFile::load(&todo_path)?;
+
+    let (section_name, task_num) = parse_task_ref(&task_ref)?;
+    let section_idx = todo
+        .find_section(&section_name)
+        .ok_or(format!("Section '{}' not found", section_name))?;
+
+    if task_num == 0 || task_num > todo.sections[section_idx].tasks.len() {
+        return Err(format!("Task {} not found in section '{}'", task_num, section_name).into());
+    }
+    let task_idx = task_num - 1;
+
+    let today = config.today()?;
+    let follow_up = follow_up
+        .map(|date| dates::parse_date(&date, today))
+        .transpose()?;
+
+    let mut task = todo.sections[section_idx].tasks.remove(task_idx);
+    task.set_inline_field("waiting_on", &person);
+    task.set_inline_field("waiting_since", &today.format("%Y-%m-%d").to_string());
+    match follow_up {
+        Some(date) => task.set_inline_field("follow_up", &date.format("%Y-%m-%d").to_string()),
+        None => task.remove_inline_field("follow_up"),
+    }
+
+    println!(
+        "Waiting on {}: {} ({}:{} -> {})",
+        person, task.text, section_name, task_num, config.waiting_section
+    );
+
+    let dest_idx = todo.get_or_create_section(&config.waiting_section);
+    todo.sections[dest_idx].tasks.push(task);
+    todo.save(&todo_path)?;
+
+    Ok(())
+}
+
+pub fn waiting(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let files = vault::todo_files(config)?;
+    let today = config.today()?;
+
+    let sections: Vec<(&str, &Section)> = files
+        .iter()
+        .filter_map(|(prefix, todo)| {
+            let idx = todo.find_section(&config.waiting_section)?;
+            Some((prefix.as_str(), &todo.sections[idx]))
+        })
+        .collect();
+    if sections.is_empty() {
+        println!("No '{}' section", config.waiting_section);
+        return Ok(());
+    }
+
+    let mut by_person: BTreeMap<String, Vec<(String, &Task)>> = BTreeMap::new();
+    for (prefix, section) in sections {
+        for (idx, task) in section.tasks.iter().enumerate() {
+            if task.done {
+                continue;
+            }
+            let person = task
+                .inline_field("waiting_on")
+                .unwrap_or_else(|| "(unassigned)".to_string());
+            let reference = format!("{}{}:{}", prefix, section.name, idx + 1);
+            by_person.entry(person).or_default().push((reference, task));
+        }
+    }
+
+    for (person, tasks) in &by_person {
+        println!("## {}", person);
+
+        for (reference, task) in tasks {
+            let date_field = |key: &str| {
+                task.inline_field(key)
+                    .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+            };
+
+            let waited = match date_field("waiting_since") {
+                Some(since) => format!(" ({} days)", (today - since).num_days()),
+                None => String::new(),
+            };
+            let follow_up = match date_field("follow_up") {
+                Some(date) if date < today => format!(" ⚠ follow-up overdue since {}", date),
+                Some(date) if date == today => " ⚠ follow up today".to_string(),
+                Some(date) => format!(" follow up {}", date),
+                None => String::new(),
+            };
+
+            let mut display = (*task).clone();
+            for key in ["waiting_on", "waiting_since", "follow_up"] {
+                display.remove_inline_field(key);
+            }
+
+            println!("  {}: {}{}{}", reference, display.text, waited, follow_up);
+        }
+
+        println!();
+    }
+
+    Ok(())
+}
+
+pub fn review(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let stdin = io::stdin();
+    review_with(config, &mut stdin.lock())
+}
+
+enum ReviewAction {
+    Keep,
+    Move(String),
+    Defer(NaiveDate),
+    Delete,
+    SkipSection,
+    Quit,
+}
+
+/// Runs the weekly review, reading one answer per stale task from `input`.
+pub fn review_with(config: &Config, input: &mut dyn BufRead) -> Result<(), Box<dyn std::error::Error>> {
+    let today = config.today()?;
+    let todo_path = config.todo_path()?;
+    let state_path = config.state_path()?;
+    let mut todo = TodoFile::load(&todo_path)?;
+    let loaded = lock::modified(&todo_path);
+    let mut quit = false;
+
+    for name in &config.review.sections {
+        let section_idx = match todo.find_section(name) {
+            Some(idx) => idx,
+            None => continue,
+        };
+        let section_name = todo.sections[section_idx].name.clone();
+
+        let open = todo.sections[section_idx].tasks.iter().filter(|t| !t.done).count();
+        let stale: Vec<usize> = todo.sections[section_idx]
+            .tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| is_stale(t, today, config.review.stale_days))
+            .map(|(idx, _)| idx)
+            .collect();
+
+        println!("## {} ({} stale of {} open)", section_name, stale.len(), open);
+
+        let mut moves: Vec<(usize, Option<String>)> = Vec::new();
+        for idx in stale {
+            let task = &mut todo.sections[section_idx].tasks[idx];
+            let age = match last_touched(task) {
+                Some(date) => format!("{} days", (today - date).num_days()),
+                None => "age unknown".to_string(),
+            };
+            println!("  {}:{}: {} ({})", section_name, idx + 1, task.text, age);
+
+            let action = prompt_review_action(input, today)?;
+            match action {
+                ReviewAction::Keep => {
+                    task.set_inline_field("reviewed", &today.format("%Y-%m-%d").to_string());
+                }
+                ReviewAction::Move(dest) => {
+                    task.set_inline_field("reviewed", &today.format("%Y-%m-%d").to_string());
+                    moves.push((idx, Some(dest)));
+                }
+                ReviewAction::Defer(until) => {
+                    task.set_field(SCHEDULED, &until.format("%Y-%m-%d").to_string());
+                    if task.inline_field("snoozed_from").is_none() {
+                        task.set_inline_field("snoozed_from", &section_name);
+                    }
+                    if !section_name.eq_ignore_ascii_case(&config.snooze_section) {
+                        moves.push((idx, Some(config.snooze_section.clone())));
+                    }
+                }
+                ReviewAction::Delete => moves.push((idx, None)),
+                ReviewAction::SkipSection => break,
+                ReviewAction::Quit => {
+                    quit = true;
+                    break;
+                }
+            }
+        }
+
+        for (idx, dest) in moves.into_iter().rev() {
+            let task = todo.sections[section_idx].tasks.remove(idx);
+            match dest {
+                Some(dest) => {
+                    println!("Moved: {} ({} -> {})", task.text, section_name, dest);
+                    let dest_idx = todo.get_or_create_section(&dest);
+                    todo.sections[dest_idx].tasks.push(task);
+                }
+                None => println!("Deleted: {} ({})", task.text, section_name),
+            }
+        }
+
+        println!();
+
+        if quit {
+            break;
+        }
+    }
+
+    // The prompts run without the lock, so only save if nothing else
+    // changed TODO.md in the meantime.
+    let _lock = lock::todo(config)?;
+    if lock::modified(&todo_path) != loaded {
+        return Err("TODO.md changed during the review; answers were not saved. Run review again".into());
+    }
+    todo.save(&todo_path)?;
+
+    if quit {
+        println!("Review stopped early; review date not recorded");
+    } else {
+        let mut state = State::load(&state_path)?;
+        state.last_review = Some(today.format("%Y-%m-%d").to_string());
+        state.save(&state_path)?;
+        println!("Review complete ({})", today);
+    }
+
+    Ok(())
+}
+
+fn prompt_review_action(
+    input: &mut dyn BufRead,
+    today: NaiveDate,
+) -> Result<ReviewAction, Box<dyn std::error::Error>> {
+    loop {
+        print!("  [k]eep, [m]ove <section>, [d]efer <date>, [x] delete, [s]kip section, [q]uit > ");
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            println!();
+            return Ok(ReviewAction::Quit);
+        }
+
+        let line = line.trim();
+        let (command, arg) = line.split_once(' ').unwrap_or((line, ""));
+        let arg = arg.trim();
+
+        match command {
+            "" | "k" | "keep" => return Ok(ReviewAction::Keep),
+            "m" | "move" if !arg.is_empty() => return Ok(ReviewAction::Move(arg.to_string())),
+            "d" | "defer" if !arg.is_empty() => match dates::parse_date(arg, today) {
+                Ok(date) => return Ok(ReviewAction::Defer(date)),
+                Err(e) => println!("  {}", e),
+            },
+            "x" | "delete" => return Ok(ReviewAction::Delete),
+            "s" | "skip" => return Ok(ReviewAction::SkipSection),
+            "q" | "quit" => return Ok(ReviewAction::Quit),
+            _ => println!("  Unrecognized answer: '{}'", line),
         }
-        let task = todo.sections[section_idx].tasks.remove(task_idx);
-        tasks_to_delete.push((task_num, task));
     }
+}
+
+/// Most recent date recorded on the task: ➕ created, last review, or when it
+/// started waiting.
+fn last_touched(task: &Task) -> Option<NaiveDate> {
+    let inline_date = |key: &str| {
+        task.inline_field(key)
+            .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+    };
+
+    [
+        task.date_field(CREATED),
+        inline_date("reviewed"),
+        inline_date("waiting_since"),
+    ]
+    .into_iter()
+    .flatten()
+    .max()
+}
+
+fn is_stale(task: &Task, today: NaiveDate, stale_days: i64) -> bool {
+    if task.done || task.date_field(SCHEDULED).is_some_and(|date| date > today) {
+        return false;
+    }
+
+    match last_touched(task) {
+        Some(date) => (today - date).num_days() >= stale_days,
+        None => true,
+    }
+}
 
-    tasks_to_delete.reverse();
+pub fn status(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let todo_path = config.todo_path()?;
+    let todo = TodoFile::load(&todo_path)?;
+    let today = config.today()?;
 
-    for (task_num, task) in &tasks_to_delete {
+    for section in &todo.sections {
+        let done = section.tasks.iter().filter(|t| t.done).count();
+        println!(
+            "{}: {} open, {} done",
+            section.name,
+            section.tasks.len() - done,
+            done
+        );
+    }
+    println!();
+
+    let state = State::load(&config.state_path()?)?;
+    let last_review = state
+        .last_review
+        .as_deref()
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+
+    match last_review {
+        Some(date) => {
+            let days = (today - date).num_days();
+            if days >= config.review.interval_days {
+                println!(
+                    "⚠ Weekly review overdue: last review {} ({} days ago). Run `mdtodo review`",
+                    date, days
+                );
+            } else {
+                println!("Last review: {} ({} days ago)", date, days);
+            }
+        }
+        None => println!("⚠ No review recorded yet. Run `mdtodo review`"),
+    }
+
+    Ok(())
+}
+
+pub fn delete(config: &Config, task_ref: String) -> Result<(), Box<dyn std::error::Error>> {
+    let (todo_path, task_ref) = vault::resolve_ref(config, &task_ref)?;
+    let _lock = lock::todo(config)?;
+    let mut todo = TodoFile::load(&todo_path)?;
+
+    let (section_name, task_nums) = parse_task_ref_multi(&task_ref)?;
+    let section_idx = todo
+        .find_section(&section_name)
+        .ok_or(format!("Section '{}' not found", section_name))?;
+
+    let deleted = remove_tasks(&mut todo, section_idx, &task_nums)?;
+
+    for (task_num, task) in &deleted {
         println!(
             "Deleted: {} ({}:{})",
             task.text, section_name, task_num
@@ -266,9 +1181,28 @@ pub fn delete(task_ref: String) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-pub fn edit(task_ref: String, new_text: String) -> Result<(), Box<dyn std::error::Error>> {
-    let config = Config::load()?;
-    let todo_path = config.todo_path()?;
+/// Removes the given 1-based tasks from a section. Returns them with their
+/// numbers in ascending order; the caller saves `todo`.
+pub(crate) fn remove_tasks(
+    todo: &mut TodoFile,
+    section_idx: usize,
+    task_nums: &[usize],
+) -> Result<Vec<(usize, Task)>, Box<dyn std::error::Error>> {
+    let section = &mut todo.sections[section_idx];
+    let mut removed = Vec::new();
+    for &task_num in task_nums.iter().rev() {
+        if task_num == 0 || task_num > section.tasks.len() {
+            return Err(format!("Task {} not found in section '{}'", task_num, section.name).into());
+        }
+        removed.push((task_num, section.tasks.remove(task_num - 1)));
+    }
+    removed.reverse();
+    Ok(removed)
+}
+
+pub fn edit(config: &Config, task_ref: String, new_text: String) -> Result<(), Box<dyn std::error::Error>> {
+    let (todo_path, task_ref) = vault::resolve_ref(config, &task_ref)?;
+    let _lock = lock::todo(config)?;
     let mut todo = TodoFile::load(&todo_path)?;
 
     let (section_name, task_num) = parse_task_ref(&task_ref)?;
@@ -281,9 +1215,7 @@ pub fn edit(task_ref: String, new_text: String) -> Result<(), Box<dyn std::error
         return Err(format!("Task {} not found in section '{}'", task_num, section_name).into());
     }
 
-    let task = &mut todo.sections[section_idx].tasks[task_idx];
-    let old_text = task.text.clone();
-    task.text = new_text.clone();
+    let old_text = std::mem::replace(&mut todo.sections[section_idx].tasks[task_idx].text, new_text.clone());
 
     todo.save(&todo_path)?;
 
@@ -295,28 +1227,376 @@ pub fn edit(task_ref: String, new_text: String) -> Result<(), Box<dyn std::error
     Ok(())
 }
 
-pub fn init() -> Result<(), Box<dyn std::error::Error>> {
-    let config = Config::load()?;
+pub fn stats(
+    config: &Config,
+    since: Option<String>,
+    until: Option<String>,
+    by: GroupBy,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let today = config.today()?;
+    let since = since.map(|d| dates::parse_date(&d, today)).transpose()?;
+    let until = until
+        .map(|d| dates::parse_date(&d, today))
+        .transpose()?
+        .unwrap_or(today);
+
+    let todo = TodoFile::load(&config.todo_path()?)?;
+    let done_log = done_log::load_all(config)?;
+
+    let completions: Vec<_> = stats::completions(&todo, &done_log)
+        .into_iter()
+        .filter(|c| since.is_none_or(|s| c.date >= s) && c.date <= until)
+        .collect();
+
+    match since {
+        Some(since) => println!("Completed: {} ({} – {})", completions.len(), since, until),
+        None => println!("Completed: {} (through {})", completions.len(), until),
+    }
+    println!();
+
+    println!("By {}:", format!("{:?}", by).to_lowercase());
+    for (key, count) in stats::group_counts(&completions, by) {
+        println!("  {:<16} {:>4}", key, count);
+    }
+    println!();
+
+    println!("Open tasks:");
+    let mut total_open = 0;
+    let mut ages = Vec::new();
+    for section in &todo.sections {
+        let open: Vec<&Task> = section.tasks.iter().filter(|t| !t.done).collect();
+        total_open += open.len();
+        ages.extend(
+            open.iter()
+                .filter_map(|t| t.date_field(CREATED))
+                .map(|created| (today - created).num_days()),
+        );
+        println!("  {:<16} {:>4}", section.name, open.len());
+    }
+    println!("  {:<16} {:>4}", "Total", total_open);
+    println!();
+
+    if ages.is_empty() {
+        println!("Average age of open tasks: n/a (no open task has a ➕ created date)");
+    } else {
+        let average = ages.iter().sum::<i64>() as f64 / ages.len() as f64;
+        println!(
+            "Average age of open tasks: {:.1} days ({} of {} dated with ➕)",
+            average,
+            ages.len(),
+            total_open
+        );
+    }
+
+    let mut per_day = BTreeMap::new();
+    for completion in &completions {
+        *per_day.entry(completion.date).or_insert(0) += 1;
+    }
+    let days = per_day.keys().copied().collect();
+    let (current, longest) = stats::streaks(&days, until);
+    println!("Streak: {} day(s) (longest {})", current, longest);
+    println!();
+
+    let weeks = since
+        .map(|s| ((until - s).num_days() / 7 + 1).clamp(1, 53))
+        .unwrap_or(12);
+    print!("{}", stats::heatmap(&per_day, until, weeks));
+
+    Ok(())
+}
+
+pub struct DoneLogQuery {
+    pub date: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub section: Option<String>,
+    pub grep: Option<String>,
+    pub json: bool,
+}
+
+pub fn done_log(config: &Config, query: DoneLogQuery) -> Result<(), Box<dyn std::error::Error>> {
+    let today = config.today()?;
+    let parse = |d: Option<String>| d.map(|d| dates::parse_date(&d, today)).transpose();
+    let date = parse(query.date)?;
+    let since = parse(query.since)?;
+    let until = parse(query.until)?;
+    let grep = query
+        .grep
+        .map(|pattern| Regex::new(&format!("(?i){}", pattern)))
+        .transpose()
+        .map_err(|e| format!("Invalid --grep pattern: {}", e))?;
+    let has_date_filter = date.is_some() || since.is_some() || until.is_some();
+
+    let done_log = done_log::load_all(config)?;
+    let matches = done_log.filtered(|day, section, task| {
+        let day = NaiveDate::parse_from_str(day, "%Y-%m-%d").ok();
+        let date_ok = match day {
+            Some(day) => {
+                date.is_none_or(|d| day == d)
+                    && since.is_none_or(|s| day >= s)
+                    && until.is_none_or(|u| day <= u)
+            }
+            None => !has_date_filter,
+        };
+        let section_ok = query
+            .section
+            .as_deref()
+            .is_none_or(|s| section.eq_ignore_ascii_case(s));
+        let grep_ok = grep.as_ref().is_none_or(|re| re.is_match(&task.text));
+        date_ok && section_ok && grep_ok
+    });
+
+    if query.json {
+        println!("{}", serde_json::to_string_pretty(&matches.entries())?);
+        return Ok(());
+    }
+
+    if matches.days.is_empty() {
+        println!("No archived tasks match");
+        return Ok(());
+    }
+
+    // Numbers follow the full group so they can be passed to `unarchive`.
+    for day in &matches.days {
+        println!("## {}", day.date);
+        for section in &day.sections {
+            println!("### {}", section.name);
+            let group = done_log
+                .days
+                .iter()
+                .find(|d| d.date == day.date)
+                .and_then(|d| d.sections.iter().find(|s| s.name == section.name));
+            for task in &section.tasks {
+                let num = group
+                    .and_then(|g| g.tasks.iter().position(|t| t == task))
+                    .map(|idx| idx + 1)
+                    .unwrap_or(0);
+                let done_marker = match &task.done_date {
+                    Some(date) => format!(" ✅ {}", date),
+                    None => String::new(),
+                };
+                println!("  {}: [x] {}{}", num, task.text, done_marker);
+            }
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Archives completed tasks from every section of TODO.md, writing the done
+/// log and TODO.md once each. With `cutoff`, only tasks done before that
+/// date are taken; tasks without a ✅ date are then left alone. Returns how
+/// many tasks were archived.
+pub fn archive_completed(config: &Config, cutoff: Option<NaiveDate>) -> Result<usize, Box<dyn std::error::Error>> {
+    let todo_path = config.todo_path()?;
+    let _lock = lock::todo(config)?;
+    let mut todo = TodoFile::load(&todo_path)?;
+
+    // Age-based sweeps leave a task alone if it was unarchived after it
+    // was completed; it only becomes due again once it is completed anew.
+    let qualifies = |task: &Task| {
+        task.done
+            && match cutoff {
+                Some(cutoff) => {
+                    let done = task
+                        .done_date
+                        .as_deref()
+                        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+                    let unarchived = task
+                        .inline_field(done_log::UNARCHIVED_FIELD)
+                        .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
+                    done.is_some_and(|d| d < cutoff && unarchived.is_none_or(|u| u < d))
+                }
+                None => true,
+            }
+    };
+
+    let mut entries = Vec::new();
+    for section in &mut todo.sections {
+        let (archived, kept): (Vec<Task>, Vec<Task>) = section.tasks.drain(..).partition(|t| qualifies(t));
+        section.tasks = kept;
+        for task in archived {
+            let done_date = task.done_date.clone().unwrap_or_else(|| "unknown".to_string());
+            entries.push((done_date, section.name.clone(), task));
+        }
+    }
+
+    if entries.is_empty() {
+        return Ok(0);
+    }
+
+    for (done_date, section, task) in &entries {
+        println!(
+            "Archived: {} ({} -> {} § {} / {})",
+            task.text,
+            section,
+            done_log::file_name(config, done_date)?,
+            done_date,
+            section
+        );
+    }
+
+    let count = entries.len();
+    done_log::record(config, entries)?;
+    todo.save(&todo_path)?;
+
+    Ok(count)
+}
+
+/// `archive --all-sections` / `--older-than`.
+pub fn archive_all(config: &Config, older_than: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let today = config.today()?;
+    let cutoff = older_than.map(|age| dates::parse_ago(&age, today)).transpose()?;
+
+    if archive_completed(config, cutoff)? == 0 {
+        println!("No completed tasks to archive");
+    }
+    Ok(())
+}
+
+pub fn compact_archive(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let _lock = lock::todo(config)?;
+    let files = done_log::compact(config)?;
+    println!(
+        "Compacted done log into {} file(s) ({} layout)",
+        files,
+        format!("{:?}", config.done_layout).to_lowercase()
+    );
+    Ok(())
+}
+
+pub fn unarchive(config: &Config, entry_ref: String, reopen: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let (date, task_ref) = entry_ref
+        .split_once('/')
+        .ok_or("Invalid reference format. Use date/Section:number (e.g. 2026-10-17/Today:1)")?;
+    let (section_name, nums) = parse_task_ref_multi(task_ref)?;
+
+    let todo_path = config.todo_path()?;
+    let _lock = lock::todo(config)?;
+    let mut todo = TodoFile::load(&todo_path)?;
+
+    let (section_name, tasks) = done_log::take(config, date, &section_name, &nums)?;
+    let section_idx = todo.get_or_create_section(&section_name);
+
+    let today = config.today_str()?;
+    for (num, mut task) in nums.iter().zip(tasks) {
+        if reopen {
+            task.done = false;
+            task.done_date = None;
+            task.remove_inline_field(done_log::UNARCHIVED_FIELD);
+        } else {
+            task.set_inline_field(done_log::UNARCHIVED_FIELD, &today);
+        }
+        println!("Unarchived: {} ({}/{}:{} -> {})", task.text, date, section_name, num, section_name);
+        todo.sections[section_idx].tasks.push(task);
+    }
+
+    todo.save(&todo_path)?;
+
+    Ok(())
+}
+
+/// Writes every task in `format` to `out`, or to stdout when unset.
+pub fn export(config: &Config, format: ExportFormat, out: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let content = export::export(&vault::todo_files(config)?, format, Utc::now())?;
+    match out {
+        Some(path) => {
+            let path = Config::expand_path(&path)?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, content)?;
+            println!("Exported to {}", path.display());
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+/// Adds the tasks in `file` to TODO.md. Tasks land in the section the
+/// format records, else `to`, else `inbox_section`. A task already in
+/// TODO.md (same 🆔, or same text without metadata) is updated rather than
+/// duplicated, so re-importing is harmless. With `replace`, TODO.md keeps
+/// only the imported tasks.
+pub fn import(
+    config: &Config,
+    format: ImportFormat,
+    file: String,
+    to: Option<String>,
+    replace: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = Config::expand_path(&file)?;
+    let content = fs::read_to_string(&path).map_err(|e| format!("Could not read '{}': {}", file, e))?;
+    let tasks = import::parse(config, &content, format)?;
+
     let todo_path = config.todo_path()?;
+    let _lock = lock::todo(config)?;
+    let mut todo = TodoFile::load(&todo_path)?;
+    let fallback = to.unwrap_or_else(|| config.inbox_section.clone());
+    let (added, updated) = import::apply(&mut todo, tasks, &fallback, replace);
 
-    if todo_path.exists() {
-        return Err(format!("TODO.md already exists at {}", todo_path.display()).into());
+    todo.save(&todo_path)?;
+    if replace {
+        println!("Replaced TODO.md tasks with {} imported task(s)", added);
+    } else {
+        println!("Imported {} new task(s), updated {}", added, updated);
     }
 
-    let template = r#"# TODO
+    Ok(())
+}
+
+/// Renders the board and the done log as a static page, written to
+/// `index.html` in `out_dir` or to stdout.
+pub fn render(config: &Config, format: RenderFormat, out_dir: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let files = vault::todo_files(config)?;
+    let done_log = done_log::load_all(config)?;
+    let content = match format {
+        RenderFormat::Html => html::page(&files, &done_log, config.today()?),
+    };
+
+    match out_dir {
+        Some(dir) => {
+            let dir = Config::expand_path(&dir)?;
+            fs::create_dir_all(&dir)?;
+            let path = dir.join("index.html");
+            fs::write(&path, content)?;
+            println!("Rendered to {}", path.display());
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
 
-## Today
+pub fn graph(config: &Config, format: GraphFormat) -> Result<(), Box<dyn std::error::Error>> {
+    print!("{}", graph::render(&vault::todo_files(config)?, format)?);
+    Ok(())
+}
 
-## Next
+pub fn pick(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    for candidate in picker::candidates(config)? {
+        println!("{}", candidate.label);
+    }
+    Ok(())
+}
 
-## Backlogs
+pub fn watch(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let todo_path = config.todo_path()?;
+    eprintln!("Watching {} (Ctrl-C to stop)", todo_path.display());
+    watch::watch(&todo_path, &mut io::stdout())
+}
 
-## Someday
+pub fn init(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let todo_path = config.todo_path()?;
 
-## Waiting
+    if todo_path.exists() {
+        return Err(format!("TODO.md already exists at {}", todo_path.display()).into());
+    }
 
-## Inbox
-"#;
+    let mut template = String::from("# TODO\n");
+    for section in &config.sections {
+        template.push_str(&format!("\n## {}\n", section));
+    }
 
     if let Some(parent) = todo_path.parent() {
         fs::create_dir_all(parent)?;
@@ -363,105 +1643,6 @@ fn parse_numbers(nums_str: &str) -> Result<Vec<usize>, Box<dyn std::error::Error
     Ok(nums)
 }
 
-fn load_done_file(path: &std::path::Path) -> Result<String, Box<dyn std::error::Error>> {
-    if path.exists() {
-        Ok(fs::read_to_string(path)?)
-    } else {
-        Ok(String::from("# Done Log\n\n"))
-    }
-}
-
-fn append_to_done(
-    mut content: String,
-    section_name: &str,
-    tasks: &[(usize, Task)],
-) -> Result<String, Box<dyn std::error::Error>> {
-    let mut date_sections: HashMap<String, HashMap<String, Vec<Task>>> = HashMap::new();
-
-    for (_, task) in tasks {
-        let date = task.done_date.as_deref().unwrap_or("unknown").to_string();
-        date_sections
-            .entry(date)
-            .or_insert_with(HashMap::new)
-            .entry(section_name.to_string())
-            .or_insert_with(Vec::new)
-            .push(task.clone());
-    }
-
-    let lines: Vec<&str> = content.lines().collect();
-    let mut result = Vec::new();
-    let mut i = 0;
-
-    if lines.is_empty() || lines[0] != "# Done Log" {
-        result.push("# Done Log".to_string());
-        result.push(String::new());
-    } else {
-        result.push(lines[0].to_string());
-        i = 1;
-        if i < lines.len() && lines[i].is_empty() {
-            result.push(String::new());
-            i += 1;
-        }
-    }
-
-    let mut existing_dates: HashMap<String, Vec<String>> = HashMap::new();
-    let mut current_date = None;
-    let mut current_section = None;
-
-    while i < lines.len() {
-        let line = lines[i];
-        if line.starts_with("## ") {
-            current_date = Some(line[3..].to_string());
-            current_section = None;
-        } else if line.starts_with("### ") {
-            current_section = Some(line[4..].to_string());
-        }
-
-        if let (Some(ref date), Some(ref section)) = (&current_date, &current_section) {
-            existing_dates
-                .entry(date.clone())
-                .or_insert_with(Vec::new)
-                .push(section.clone());
-        }
-
-        i += 1;
-    }
-
-    content.clear();
-    for line in &result {
-        content.push_str(line);
-        content.push('\n');
-    }
-
-    let mut dates: Vec<String> = date_sections.keys().cloned().collect();
-    dates.sort();
-    dates.reverse();
-
-    for date in dates {
-        content.push_str(&format!("## {}\n\n", date));
-
-        if let Some(sections) = date_sections.get(&date) {
-            for (section, tasks) in sections {
-                content.push_str(&format!("### {}\n", section));
-                for task in tasks {
-                    content.push_str(&format!("{}\n", task.to_markdown()));
-                }
-                content.push('\n');
-            }
-        }
-    }
-
-    Ok(content)
-}
-
-fn save_done_file(content: &str, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-    fs::write(path, content)?;
-    Ok(())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
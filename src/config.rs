@@ -1,6 +1,7 @@
-use chrono::{Local, Utc};
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use chrono_tz::Tz;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -10,8 +11,261 @@ pub struct Config {
     pub todo_path: String,
     #[serde(default = "default_done_path")]
     pub done_path: String,
+    #[serde(default)]
+    pub done_layout: DoneLayout,
+    /// Directory for the per-month and per-year done log files.
+    #[serde(default = "default_done_dir")]
+    pub done_dir: String,
+    #[serde(default)]
+    pub done_format: DoneFormat,
+    /// Heading of the block archived tasks go under in the `daily` layout.
+    #[serde(default = "default_daily_done_heading")]
+    pub daily_done_heading: String,
     #[serde(default = "default_timezone")]
     pub timezone: String,
+    #[serde(default = "default_state_path")]
+    pub state_path: String,
+    #[serde(default = "default_sections")]
+    pub sections: Vec<String>,
+    #[serde(default)]
+    pub vault_path: Option<String>,
+    #[serde(default = "default_vault_include")]
+    pub vault_include: Vec<String>,
+    #[serde(default = "default_vault_exclude")]
+    pub vault_exclude: Vec<String>,
+    #[serde(default = "default_daily_path")]
+    pub daily_path: String,
+    #[serde(default = "default_daily_date_format")]
+    pub daily_date_format: String,
+    #[serde(default)]
+    pub daily_template: Option<String>,
+    #[serde(default = "default_daily_heading")]
+    pub daily_heading: String,
+    #[serde(default)]
+    pub rollover: RolloverConfig,
+    #[serde(default = "default_snooze_section")]
+    pub snooze_section: String,
+    #[serde(default)]
+    pub auto_wake: bool,
+    #[serde(default = "default_waiting_section")]
+    pub waiting_section: String,
+    #[serde(default)]
+    pub blocked_done: BlockedDone,
+    #[serde(default)]
+    pub auto_archive: AutoArchive,
+    /// Age in days beyond which `after_days` archives a completed task.
+    #[serde(default = "default_auto_archive_days")]
+    pub auto_archive_days: i64,
+    /// External picker (e.g. `fzf`) used for `?` task references. The
+    /// built-in picker is used when unset.
+    #[serde(default)]
+    pub picker_command: Option<String>,
+    #[serde(default = "default_inbox_section")]
+    pub inbox_section: String,
+    #[serde(default)]
+    pub drop_dir: Option<String>,
+    /// Where `capture --drop-dir` moves ingested files. They are deleted
+    /// when unset.
+    #[serde(default)]
+    pub drop_archive_dir: Option<String>,
+    /// Append `➕ YYYY-MM-DD` to tasks created with `add`.
+    #[serde(default)]
+    pub stamp_created: bool,
+    #[serde(default)]
+    pub review: ReviewConfig,
+    #[serde(default)]
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+}
+
+/// A named `[profiles.<name>]` table. Any field left out falls back to the
+/// top-level value.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Profile {
+    pub todo_path: Option<String>,
+    pub done_path: Option<String>,
+    pub done_dir: Option<String>,
+    pub timezone: Option<String>,
+    pub state_path: Option<String>,
+    pub sections: Option<Vec<String>>,
+    pub vault_path: Option<String>,
+}
+
+/// How the done log is split across files.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DoneLayout {
+    /// Everything in `done_path`.
+    #[default]
+    Single,
+    /// One file per month: `done_dir/2026-10.md`.
+    Monthly,
+    /// One file per year: `done_dir/2026.md`.
+    Yearly,
+    /// A block under `daily_done_heading` in the daily note of each date.
+    Daily,
+}
+
+/// How entries are grouped inside a done log file.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DoneGrouping {
+    /// `## date` / `### section`.
+    #[default]
+    DateSection,
+    /// `## section` / `### date`.
+    SectionDate,
+    /// `## 2026-W42` / `### section`.
+    Week,
+    /// A single list; each task carries `[section:: …]`.
+    Flat,
+}
+
+/// The `[done_format]` table.
+#[derive(Deserialize, Debug, Clone)]
+pub struct DoneFormat {
+    #[serde(default)]
+    pub group_by: DoneGrouping,
+    /// Title of newly created done log files.
+    #[serde(default = "default_done_title")]
+    pub title: String,
+    /// chrono format of date headings. It must include the year to be read
+    /// back; `YYYY-MM-DD` headings are always accepted.
+    #[serde(default = "default_done_date_format")]
+    pub date_format: String,
+}
+
+impl Default for DoneFormat {
+    fn default() -> Self {
+        Self {
+            group_by: DoneGrouping::default(),
+            title: default_done_title(),
+            date_format: default_done_date_format(),
+        }
+    }
+}
+
+fn default_done_title() -> String {
+    "Done Log".to_string()
+}
+
+fn default_done_date_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+/// What `done` does with a task whose ⛔ blockers are still open.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockedDone {
+    /// Complete it and print a warning.
+    #[default]
+    Warn,
+    /// Fail unless `--force` is given.
+    Refuse,
+}
+
+/// When completed tasks are archived without an explicit `archive`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoArchive {
+    #[default]
+    Off,
+    /// As soon as `done` marks a task.
+    OnDone,
+    /// On every command, once a task has been done for more than
+    /// `auto_archive_days`.
+    AfterDays,
+    /// During `rollover`, from every section.
+    OnRollover,
+}
+
+/// The `[rollover]` table.
+#[derive(Deserialize, Debug, Clone)]
+pub struct RolloverConfig {
+    #[serde(default = "default_rollover_source")]
+    pub source: String,
+    /// Section that open tasks move to. When unset they stay in `source`.
+    #[serde(default)]
+    pub destination: Option<String>,
+    /// Stamp open tasks with a `[carried:: N]` counter.
+    #[serde(default = "default_true")]
+    pub count_carried: bool,
+    #[serde(default = "default_true")]
+    pub archive_done: bool,
+}
+
+impl Default for RolloverConfig {
+    fn default() -> Self {
+        Self {
+            source: default_rollover_source(),
+            destination: None,
+            count_carried: true,
+            archive_done: true,
+        }
+    }
+}
+
+/// The `[review]` table.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ReviewConfig {
+    #[serde(default = "default_review_sections")]
+    pub sections: Vec<String>,
+    /// Tasks untouched for this many days are shown during review.
+    #[serde(default = "default_stale_days")]
+    pub stale_days: i64,
+    /// `status` nags once the last review is older than this.
+    #[serde(default = "default_review_interval_days")]
+    pub interval_days: i64,
+}
+
+impl Default for ReviewConfig {
+    fn default() -> Self {
+        Self {
+            sections: default_review_sections(),
+            stale_days: default_stale_days(),
+            interval_days: default_review_interval_days(),
+        }
+    }
+}
+
+fn default_review_sections() -> Vec<String> {
+    ["Inbox", "Waiting", "Next", "Someday"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_stale_days() -> i64 {
+    14
+}
+
+fn default_review_interval_days() -> i64 {
+    7
+}
+
+fn default_rollover_source() -> String {
+    "Today".to_string()
+}
+
+fn default_snooze_section() -> String {
+    "Someday".to_string()
+}
+
+fn default_waiting_section() -> String {
+    "Waiting".to_string()
+}
+
+fn default_auto_archive_days() -> i64 {
+    7
+}
+
+fn default_inbox_section() -> String {
+    "Inbox".to_string()
+}
+
+fn default_true() -> bool {
+    true
 }
 
 fn default_todo_path() -> String {
@@ -22,31 +276,138 @@ fn default_done_path() -> String {
     "./done_list.md".to_string()
 }
 
+fn default_done_dir() -> String {
+    "./done".to_string()
+}
+
+fn default_daily_done_heading() -> String {
+    "Done".to_string()
+}
+
 fn default_timezone() -> String {
     "Local".to_string()
 }
 
+fn default_vault_include() -> Vec<String> {
+    vec!["**/*.md".to_string()]
+}
+
+fn default_vault_exclude() -> Vec<String> {
+    vec![".obsidian/**".to_string(), ".trash/**".to_string()]
+}
+
+fn default_daily_path() -> String {
+    "./Daily/{date}.md".to_string()
+}
+
+fn default_daily_date_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+fn default_daily_heading() -> String {
+    "Tasks".to_string()
+}
+
+fn default_state_path() -> String {
+    "./.mdtodo_state.toml".to_string()
+}
+
+fn default_sections() -> Vec<String> {
+    ["Today", "Next", "Backlogs", "Someday", "Waiting", "Inbox"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             todo_path: default_todo_path(),
             done_path: default_done_path(),
+            done_layout: DoneLayout::default(),
+            done_dir: default_done_dir(),
+            done_format: DoneFormat::default(),
+            daily_done_heading: default_daily_done_heading(),
             timezone: default_timezone(),
+            state_path: default_state_path(),
+            sections: default_sections(),
+            vault_path: None,
+            vault_include: default_vault_include(),
+            vault_exclude: default_vault_exclude(),
+            daily_path: default_daily_path(),
+            daily_date_format: default_daily_date_format(),
+            daily_template: None,
+            daily_heading: default_daily_heading(),
+            rollover: RolloverConfig::default(),
+            snooze_section: default_snooze_section(),
+            auto_wake: false,
+            waiting_section: default_waiting_section(),
+            blocked_done: BlockedDone::default(),
+            auto_archive: AutoArchive::default(),
+            auto_archive_days: default_auto_archive_days(),
+            picker_command: None,
+            inbox_section: default_inbox_section(),
+            drop_dir: None,
+            drop_archive_dir: None,
+            stamp_created: false,
+            review: ReviewConfig::default(),
+            default_profile: None,
+            profiles: HashMap::new(),
         }
     }
 }
 
 impl Config {
-    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn load(profile: Option<&str>) -> Result<Self, Box<dyn std::error::Error>> {
         let config_path = Self::config_path()?;
 
-        if !config_path.exists() {
-            return Ok(Config::default());
+        let config = if config_path.exists() {
+            let content = fs::read_to_string(config_path)?;
+            toml::from_str(&content)?
+        } else {
+            Config::default()
+        };
+
+        config.with_profile(profile)
+    }
+
+    /// Applies the named profile (or `default_profile` when `name` is `None`)
+    /// on top of the top-level settings.
+    pub fn with_profile(mut self, name: Option<&str>) -> Result<Self, Box<dyn std::error::Error>> {
+        let name = match name.map(str::to_string).or_else(|| self.default_profile.clone()) {
+            Some(name) => name,
+            None => return Ok(self),
+        };
+
+        let profile = self
+            .profiles
+            .get(&name)
+            .cloned()
+            .ok_or(format!("Profile '{}' not found", name))?;
+
+        if let Some(todo_path) = profile.todo_path {
+            self.todo_path = todo_path;
+        }
+        if let Some(done_path) = profile.done_path {
+            self.done_path = done_path;
+        }
+        if let Some(done_dir) = profile.done_dir {
+            self.done_dir = done_dir;
+        }
+        if let Some(timezone) = profile.timezone {
+            self.timezone = timezone;
+        }
+        if let Some(state_path) = profile.state_path {
+            self.state_path = state_path;
+        }
+        if let Some(sections) = profile.sections {
+            self.sections = sections;
+        }
+        if let Some(vault_path) = profile.vault_path {
+            self.vault_path = Some(vault_path);
         }
 
-        let content = fs::read_to_string(config_path)?;
-        let config: Config = toml::from_str(&content)?;
-        Ok(config)
+        Ok(self)
     }
 
     fn config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
@@ -58,10 +419,10 @@ impl Config {
     }
 
     pub fn expand_path(path: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
-        if path.starts_with("~/") {
+        if let Some(rest) = path.strip_prefix("~/") {
             let home = dirs::home_dir()
                 .ok_or("Could not find home directory")?;
-            Ok(home.join(&path[2..]))
+            Ok(home.join(rest))
         } else {
             Ok(PathBuf::from(path))
         }
@@ -75,15 +436,36 @@ impl Config {
         Self::expand_path(&self.done_path)
     }
 
-    pub fn today_str(&self) -> Result<String, Box<dyn std::error::Error>> {
+    pub fn done_dir(&self) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        Self::expand_path(&self.done_dir)
+    }
+
+    pub fn state_path(&self) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        Self::expand_path(&self.state_path)
+    }
+
+    pub fn vault_path(&self) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+        self.vault_path.as_deref().map(Self::expand_path).transpose()
+    }
+
+    pub fn today(&self) -> Result<NaiveDate, Box<dyn std::error::Error>> {
+        self.local_date(Utc::now())
+    }
+
+    /// The calendar date `instant` falls on in `timezone`.
+    pub fn local_date(&self, instant: DateTime<Utc>) -> Result<NaiveDate, Box<dyn std::error::Error>> {
         if self.timezone == "Local" {
-            Ok(Local::now().format("%Y-%m-%d").to_string())
+            Ok(instant.with_timezone(&Local).date_naive())
         } else {
             let tz: Tz = self.timezone.parse()
                 .map_err(|_| format!("Invalid timezone: '{}'", self.timezone))?;
-            Ok(Utc::now().with_timezone(&tz).format("%Y-%m-%d").to_string())
+            Ok(instant.with_timezone(&tz).date_naive())
         }
     }
+
+    pub fn today_str(&self) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(self.today()?.format("%Y-%m-%d").to_string())
+    }
 }
 
 #[cfg(test)]
@@ -96,6 +478,49 @@ mod tests {
         assert_eq!(config.todo_path, "./TODO.md");
         assert_eq!(config.done_path, "./done_list.md");
         assert_eq!(config.timezone, "Local");
+        assert_eq!(config.sections[0], "Today");
+        assert!(config.profiles.is_empty());
+    }
+
+    #[test]
+    fn test_profile_overrides() {
+        let content = r#"
+todo_path = "~/TODO.md"
+timezone = "Asia/Tokyo"
+
+[profiles.work]
+todo_path = "~/work/TODO.md"
+sections = ["Inbox", "Sprint"]
+"#;
+        let config: Config = toml::from_str(content).unwrap();
+
+        let work = config.clone().with_profile(Some("work")).unwrap();
+        assert_eq!(work.todo_path, "~/work/TODO.md");
+        assert_eq!(work.done_path, "./done_list.md");
+        assert_eq!(work.timezone, "Asia/Tokyo");
+        assert_eq!(work.sections, vec!["Inbox", "Sprint"]);
+
+        let plain = config.with_profile(None).unwrap();
+        assert_eq!(plain.todo_path, "~/TODO.md");
+    }
+
+    #[test]
+    fn test_default_profile() {
+        let content = r#"
+default_profile = "personal"
+
+[profiles.personal]
+todo_path = "~/personal/TODO.md"
+"#;
+        let config: Config = toml::from_str(content).unwrap();
+        let resolved = config.with_profile(None).unwrap();
+        assert_eq!(resolved.todo_path, "~/personal/TODO.md");
+    }
+
+    #[test]
+    fn test_unknown_profile() {
+        let result = Config::default().with_profile(Some("missing"));
+        assert!(result.unwrap_err().to_string().contains("Profile 'missing' not found"));
     }
 
     #[test]
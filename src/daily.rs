@@ -0,0 +1,261 @@
+use crate::config::Config;
+use crate::markdown::Task;
+use chrono::{Duration, NaiveDate};
+use glob::Pattern;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+/// How far back `previous_note` looks for an earlier daily note.
+const LOOKBACK_DAYS: i64 = 365;
+
+/// Formats `date` with `daily_date_format`, reporting a bad format string as
+/// an error instead of panicking.
+pub fn format_date(config: &Config, date: NaiveDate) -> Result<String, Box<dyn std::error::Error>> {
+    let mut formatted = String::new();
+    write!(formatted, "{}", date.format(&config.daily_date_format))
+        .map_err(|_| format!("Invalid daily_date_format: '{}'", config.daily_date_format))?;
+    Ok(formatted)
+}
+
+pub fn note_path(config: &Config, date: NaiveDate) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = config.daily_path.replace("{date}", &format_date(config, date)?);
+    Config::expand_path(&path)
+}
+
+pub fn render_template(config: &Config, date: NaiveDate) -> Result<String, Box<dyn std::error::Error>> {
+    let template = match &config.daily_template {
+        Some(path) => fs::read_to_string(Config::expand_path(path)?)
+            .map_err(|e| format!("Could not read daily_template '{}': {}", path, e))?,
+        None => "# {date}\n\n## {heading}\n".to_string(),
+    };
+
+    Ok(template
+        .replace("{date}", &format_date(config, date)?)
+        .replace("{heading}", &config.daily_heading))
+}
+
+/// Returns the path of the daily note for `date`, creating it from the
+/// template first if it does not exist yet. The flag is `true` when the note
+/// was created.
+pub fn ensure_note(config: &Config, date: NaiveDate) -> Result<(PathBuf, bool), Box<dyn std::error::Error>> {
+    let path = note_path(config, date)?;
+    if path.exists() {
+        return Ok((path, false));
+    }
+
+    let content = render_template(config, date)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, content)?;
+
+    Ok((path, true))
+}
+
+/// Finds the most recent existing daily note strictly before `date`.
+pub fn previous_note(
+    config: &Config,
+    date: NaiveDate,
+) -> Result<Option<(NaiveDate, PathBuf)>, Box<dyn std::error::Error>> {
+    for days_back in 1..=LOOKBACK_DAYS {
+        let candidate = date - Duration::days(days_back);
+        let path = note_path(config, candidate)?;
+        if path.exists() {
+            return Ok(Some((candidate, path)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Every existing daily note matching `daily_path`, oldest first.
+pub fn notes(config: &Config) -> Result<Vec<(NaiveDate, PathBuf)>, Box<dyn std::error::Error>> {
+    let template = Config::expand_path(&config.daily_path)?.to_string_lossy().to_string();
+    let (prefix, suffix) = template
+        .split_once("{date}")
+        .ok_or(format!("daily_path has no {{date}} placeholder: '{}'", config.daily_path))?;
+    // One `*` per path component, so formats like `%Y/%m/%d` still match.
+    let sample = format_date(config, NaiveDate::default())?;
+    let wildcard = vec!["*"; sample.split('/').count()].join("/");
+    let pattern = format!("{}{}{}", Pattern::escape(prefix), wildcard, Pattern::escape(suffix));
+
+    let mut result = Vec::new();
+    for entry in glob::glob(&pattern)? {
+        let path = entry?;
+        let full = path.to_string_lossy();
+        let date = full
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_suffix(suffix))
+            .and_then(|date| NaiveDate::parse_from_str(date, &config.daily_date_format).ok());
+        if let Some(date) = date {
+            result.push((date, path));
+        }
+    }
+    result.sort();
+
+    Ok(result)
+}
+
+/// Line range of the `## heading` block in a daily note: the heading line
+/// up to (not including) the next `#` or `##` heading.
+pub fn block(note: &str, heading: &str) -> Option<(usize, usize)> {
+    let lines: Vec<&str> = note.lines().collect();
+    let start = lines
+        .iter()
+        .position(|line| line.strip_prefix("## ").is_some_and(|h| h.trim() == heading))?;
+    let end = lines[start + 1..]
+        .iter()
+        .position(|line| line.starts_with("# ") || line.starts_with("## "))
+        .map(|offset| start + 1 + offset)
+        .unwrap_or(lines.len());
+    Some((start, end))
+}
+
+/// `note` with `tasks` added to its `## heading` block, after the block's
+/// last task (or last non-blank line). Every other line is left as it is;
+/// the block is appended when missing.
+pub fn insert_tasks(note: &str, heading: &str, tasks: &[Task]) -> String {
+    let task_lines: String = tasks.iter().map(|task| format!("{}\n", task.to_markdown())).collect();
+    let lines: Vec<&str> = note.lines().collect();
+
+    let (start, end) = match block(note, heading) {
+        Some(range) => range,
+        None => {
+            let mut result = note.to_string();
+            if !note.is_empty() && !note.ends_with("\n\n") {
+                result.push_str(if note.ends_with('\n') { "\n" } else { "\n\n" });
+            }
+            result.push_str(&format!("## {}\n", heading));
+            result.push_str(&task_lines);
+            return result;
+        }
+    };
+    let body = &lines[start + 1..end];
+    let at = body
+        .iter()
+        .rposition(|line| Task::from_line(line).is_some())
+        .or_else(|| body.iter().rposition(|line| !line.trim().is_empty()))
+        .map_or(start + 1, |i| start + 2 + i);
+
+    let mut result = String::new();
+    for line in &lines[..at] {
+        result.push_str(&format!("{}\n", line));
+    }
+    result.push_str(&task_lines);
+    for line in &lines[at..] {
+        result.push_str(&format!("{}\n", line));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn daily_config(temp: &TempDir) -> Config {
+        Config {
+            daily_path: format!("{}/Daily/{{date}}.md", temp.path().display()),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_note_path_uses_date_format() {
+        let config = Config {
+            daily_path: "./Journal/{date}.md".to_string(),
+            daily_date_format: "%Y/%m/%d".to_string(),
+            ..Config::default()
+        };
+        let date = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        assert_eq!(
+            note_path(&config, date).unwrap(),
+            PathBuf::from("./Journal/2026/10/17.md")
+        );
+    }
+
+    #[test]
+    fn test_invalid_date_format() {
+        let config = Config {
+            daily_date_format: "%Q".to_string(),
+            ..Config::default()
+        };
+        let date = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        assert!(format_date(&config, date).is_err());
+    }
+
+    #[test]
+    fn test_ensure_note_creates_from_template() {
+        let temp = TempDir::new().unwrap();
+        let template_path = temp.path().join("template.md");
+        fs::write(&template_path, "# Daily {date}\n\n## {heading}\n\n## Notes\n").unwrap();
+        let config = Config {
+            daily_template: Some(template_path.to_string_lossy().to_string()),
+            ..daily_config(&temp)
+        };
+        let date = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+
+        let (path, created) = ensure_note(&config, date).unwrap();
+        assert!(created);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# Daily 2026-10-17\n\n## Tasks\n\n## Notes\n"
+        );
+
+        let (_, created_again) = ensure_note(&config, date).unwrap();
+        assert!(!created_again);
+    }
+
+    #[test]
+    fn test_previous_note() {
+        let temp = TempDir::new().unwrap();
+        let config = daily_config(&temp);
+        let today = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        assert!(previous_note(&config, today).unwrap().is_none());
+
+        let earlier = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        ensure_note(&config, earlier).unwrap();
+        ensure_note(&config, today).unwrap();
+
+        let (date, _) = previous_note(&config, today).unwrap().unwrap();
+        assert_eq!(date, earlier);
+    }
+
+    #[test]
+    fn test_notes_lists_dated_files() {
+        let temp = TempDir::new().unwrap();
+        let config = Config {
+            daily_path: format!("{}/Journal/{{date}}.md", temp.path().display()),
+            daily_date_format: "%Y/%m/%d".to_string(),
+            ..Config::default()
+        };
+        let later = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        let earlier = NaiveDate::from_ymd_opt(2026, 9, 30).unwrap();
+        ensure_note(&config, later).unwrap();
+        ensure_note(&config, earlier).unwrap();
+        fs::write(temp.path().join("Journal/2026/10/notes.md"), "").unwrap();
+
+        let dates: Vec<NaiveDate> = notes(&config).unwrap().into_iter().map(|(d, _)| d).collect();
+        assert_eq!(dates, vec![earlier, later]);
+    }
+
+    #[test]
+    fn test_insert_tasks_keeps_journal() {
+        let note = "# 2026-10-18\n\nSlept well.\n\n## Tasks\n- [ ] Existing\n  - [ ] Nested\n\nSome thoughts.\n\n## Journal\nLong entry.\n";
+        let task = Task::from_line("- [ ] New").unwrap();
+
+        assert_eq!(
+            insert_tasks(note, "Tasks", std::slice::from_ref(&task)),
+            "# 2026-10-18\n\nSlept well.\n\n## Tasks\n- [ ] Existing\n  - [ ] Nested\n- [ ] New\n\nSome thoughts.\n\n## Journal\nLong entry.\n"
+        );
+        assert_eq!(
+            insert_tasks(note, "Journal", std::slice::from_ref(&task)),
+            note.replace("Long entry.\n", "Long entry.\n- [ ] New\n")
+        );
+        assert_eq!(
+            insert_tasks("# Day\n", "Later", &[task]),
+            "# Day\n\n## Later\n- [ ] New\n"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use chrono::{Datelike, Duration, NaiveDate, TimeDelta, Weekday};
+
+/// Parses a date argument: `YYYY-MM-DD`, `today`, `tomorrow`, `yesterday`, a weekday name
+/// (the next such day), or an offset like `+3d` / `+2w` / `-30d`.
+pub fn parse_date(input: &str, today: NaiveDate) -> Result<NaiveDate, Box<dyn std::error::Error>> {
+    let input = input.trim().to_lowercase();
+
+    match input.as_str() {
+        "today" => return Ok(today),
+        "tomorrow" => return Ok(today + Duration::days(1)),
+        "yesterday" => return Ok(today - Duration::days(1)),
+        _ => {}
+    }
+
+    if let Ok(weekday) = input.parse::<Weekday>() {
+        let ahead = (weekday.num_days_from_monday() as i64
+            - today.weekday().num_days_from_monday() as i64
+            + 7)
+            % 7;
+        let ahead = if ahead == 0 { 7 } else { ahead };
+        return Ok(today + Duration::days(ahead));
+    }
+
+    let signed = input
+        .strip_prefix('+')
+        .map(|offset| (offset, 1))
+        .or_else(|| input.strip_prefix('-').map(|offset| (offset, -1)));
+    if let Some((offset, sign)) = signed {
+        let (num, weeks) = match (offset.strip_suffix('d'), offset.strip_suffix('w')) {
+            (Some(num), _) => (num, false),
+            (_, Some(num)) => (num, true),
+            _ => return Err(format!("Invalid date: '{}'", input).into()),
+        };
+        let invalid = || format!("Invalid date: '{}'", input);
+        let num: i64 = num.parse::<i64>().map_err(|_| invalid())? * sign;
+        let days = if weeks { num.checked_mul(7).ok_or_else(invalid)? } else { num };
+        return TimeDelta::try_days(days)
+            .and_then(|delta| today.checked_add_signed(delta))
+            .ok_or_else(|| invalid().into());
+    }
+
+    NaiveDate::parse_from_str(&input, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date: '{}'. Use YYYY-MM-DD", input).into())
+}
+
+/// The date `input` ago, for age thresholds: `7d` / `2w`, or anything
+/// `parse_date` accepts.
+pub fn parse_ago(input: &str, today: NaiveDate) -> Result<NaiveDate, Box<dyn std::error::Error>> {
+    let trimmed = input.trim();
+    if trimmed.starts_with(|c: char| c.is_ascii_digit()) && trimmed.ends_with(['d', 'w']) {
+        return parse_date(&format!("-{}", trimmed), today);
+    }
+    parse_date(trimmed, today)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_date() {
+        // 2026-10-17 is a Saturday
+        let today = date(2026, 10, 17);
+        assert_eq!(parse_date("2026-11-01", today).unwrap(), date(2026, 11, 1));
+        assert_eq!(parse_date("today", today).unwrap(), today);
+        assert_eq!(parse_date("Tomorrow", today).unwrap(), date(2026, 10, 18));
+        assert_eq!(parse_date("yesterday", today).unwrap(), date(2026, 10, 16));
+        assert_eq!(parse_date("+3d", today).unwrap(), date(2026, 10, 20));
+        assert_eq!(parse_date("+2w", today).unwrap(), date(2026, 10, 31));
+        assert_eq!(parse_date("-30d", today).unwrap(), date(2026, 9, 17));
+        assert_eq!(parse_date("monday", today).unwrap(), date(2026, 10, 19));
+        assert_eq!(parse_date("sat", today).unwrap(), date(2026, 10, 24));
+        assert!(parse_date("someday", today).is_err());
+        assert!(parse_date("+3y", today).is_err());
+        assert!(parse_date("+3é", today).is_err());
+        assert!(parse_date("-", today).is_err());
+        assert!(parse_date("+99999999999d", today).is_err());
+        assert!(parse_date("+9999999999999999d", today).is_err());
+        assert!(parse_date("-999999999d", today).is_err());
+        assert!(parse_date("+99999999999w", today).is_err());
+        assert!(parse_date("+9999999999999999999w", today).is_err());
+    }
+
+    #[test]
+    fn test_parse_ago() {
+        let today = date(2026, 10, 17);
+        assert_eq!(parse_ago("7d", today).unwrap(), date(2026, 10, 10));
+        assert_eq!(parse_ago("2w", today).unwrap(), date(2026, 10, 3));
+        assert_eq!(parse_ago("2026-10-01", today).unwrap(), date(2026, 10, 1));
+        assert!(parse_ago("7y", today).is_err());
+    }
+}
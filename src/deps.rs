@@ -0,0 +1,135 @@
+use crate::markdown::{Task, TodoFile, DEPENDS_ON, ID, SCHEDULED};
+use chrono::NaiveDate;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+/// Ids listed in the task's `⛔ id1,id2` field.
+pub fn blocker_ids(task: &Task) -> Vec<String> {
+    task.field(DEPENDS_ON)
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `🆔` ids of every task in `todos`.
+pub fn all_ids<'a>(todos: impl IntoIterator<Item = &'a TodoFile>) -> HashSet<String> {
+    ids(todos, |_| true)
+}
+
+/// `🆔` ids of tasks that are not done yet.
+pub fn open_ids<'a>(todos: impl IntoIterator<Item = &'a TodoFile>) -> HashSet<String> {
+    ids(todos, |task| !task.done)
+}
+
+fn ids<'a>(todos: impl IntoIterator<Item = &'a TodoFile>, keep: impl Fn(&Task) -> bool) -> HashSet<String> {
+    todos
+        .into_iter()
+        .flat_map(|todo| todo.sections.iter().flat_map(|s| s.tasks.iter()))
+        .filter(|task| keep(task))
+        .filter_map(|task| task.field(ID))
+        .collect()
+}
+
+/// Blockers of `task` that are still open. Ids that match no open task,
+/// such as archived ones, do not block.
+pub fn open_blockers(task: &Task, open_ids: &HashSet<String>) -> Vec<String> {
+    blocker_ids(task)
+        .into_iter()
+        .filter(|id| open_ids.contains(id))
+        .collect()
+}
+
+/// Open, unblocked and not scheduled for a later day.
+pub fn is_ready(task: &Task, open_ids: &HashSet<String>, today: NaiveDate) -> bool {
+    !task.done
+        && open_blockers(task, open_ids).is_empty()
+        && task.date_field(SCHEDULED).is_none_or(|date| date <= today)
+}
+
+/// A six-character id derived from `seed`, distinct from every id in `taken`.
+pub fn new_id(seed: &str, taken: &HashSet<String>) -> String {
+    const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+
+    for attempt in 0u64.. {
+        let mut hasher = DefaultHasher::new();
+        (seed, attempt).hash(&mut hasher);
+        let mut n = hasher.finish();
+
+        let id: String = (0..6)
+            .map(|_| {
+                let c = ALPHABET[(n % ALPHABET.len() as u64) as usize] as char;
+                n /= ALPHABET.len() as u64;
+                c
+            })
+            .collect();
+        if !taken.contains(&id) {
+            return id;
+        }
+    }
+
+    unreachable!("ran out of ids")
+}
+
+/// Adds `id` to the task's ⛔ list unless it is already there.
+pub fn add_blocker(task: &mut Task, id: &str) {
+    let mut ids = blocker_ids(task);
+    if !ids.iter().any(|existing| existing == id) {
+        ids.push(id.to_string());
+        task.set_field(DEPENDS_ON, &ids.join(","));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(text: &str, done: bool) -> Task {
+        Task {
+            text: text.to_string(),
+            done,
+            done_date: None,
+        }
+    }
+
+    #[test]
+    fn test_blockers_and_readiness() {
+        let todo = TodoFile::parse(
+            "## Today\n- [ ] Write spec 🆔 spec01\n- [x] Book room 🆔 room01\n- [ ] Implement ⛔ spec01,room01\n- [ ] Deploy ⛔ gone99\n- [ ] Later ⏳ 2026-12-01\n",
+        )
+        .unwrap();
+        let open = open_ids([&todo]);
+        assert_eq!(open, HashSet::from(["spec01".to_string()]));
+        assert_eq!(all_ids([&todo]).len(), 2);
+
+        let tasks = &todo.sections[0].tasks;
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        assert_eq!(blocker_ids(&tasks[2]), vec!["spec01", "room01"]);
+        assert_eq!(open_blockers(&tasks[2], &open), vec!["spec01"]);
+        assert!(is_ready(&tasks[0], &open, today));
+        assert!(!is_ready(&tasks[1], &open, today));
+        assert!(!is_ready(&tasks[2], &open, today));
+        assert!(is_ready(&tasks[3], &open, today));
+        assert!(!is_ready(&tasks[4], &open, today));
+    }
+
+    #[test]
+    fn test_add_blocker_and_new_id() {
+        let mut t = task("Implement", false);
+        add_blocker(&mut t, "abc123");
+        add_blocker(&mut t, "def456");
+        add_blocker(&mut t, "abc123");
+        assert_eq!(t.text, "Implement ⛔ abc123,def456");
+
+        let first = new_id("Write spec", &HashSet::new());
+        assert_eq!(first.len(), 6);
+        let second = new_id("Write spec", &HashSet::from([first.clone()]));
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,918 @@
+use crate::config::{Config, DoneFormat, DoneGrouping, DoneLayout};
+use crate::daily;
+use crate::markdown::Task;
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct DoneSection {
+    pub name: String,
+    pub tasks: Vec<Task>,
+    pub other_lines: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DoneDay {
+    pub date: String,
+    pub sections: Vec<DoneSection>,
+}
+
+/// One archived task with the group it was filed under.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DoneEntry {
+    pub date: String,
+    pub section: String,
+    pub text: String,
+    pub done_date: Option<String>,
+}
+
+/// done_list.md parsed into `## date` / `### section` groups.
+#[derive(Debug, Default)]
+pub struct DoneLog {
+    pub header_lines: Vec<String>,
+    pub days: Vec<DoneDay>,
+}
+
+impl DoneLog {
+    pub fn load(path: &Path, format: &DoneFormat) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Ok(DoneLog::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(Self::parse_with(&content, format))
+    }
+
+    pub fn parse(content: &str) -> Self {
+        Self::parse_with(content, &DoneFormat::default())
+    }
+
+    /// Reads a done log written with any `DoneGrouping`, so changing
+    /// `group_by` never strands existing files. `format` is only consulted
+    /// for the date heading format.
+    pub fn parse_with(content: &str, format: &DoneFormat) -> Self {
+        let mut log = DoneLog::default();
+        let mut h2: Option<Heading> = None;
+        let mut h3: Option<String> = None;
+        let mut last_group: Option<(String, String)> = None;
+        let mut seen_entry = false;
+
+        for line in content.lines() {
+            if let Some(text) = line.strip_prefix("## ") {
+                h2 = Some(Heading::classify(text.trim(), format));
+                h3 = None;
+                last_group = None;
+                seen_entry = true;
+                continue;
+            }
+            if let Some(text) = line.strip_prefix("### ") {
+                if h2.is_some() {
+                    h3 = Some(text.trim().to_string());
+                    last_group = None;
+                    continue;
+                }
+            }
+
+            if let Some(mut task) = Task::from_line(line) {
+                seen_entry = true;
+                let task_date = |task: &Task| {
+                    task.done_date.clone().unwrap_or_else(|| UNKNOWN_DATE.to_string())
+                };
+                let (date, section) = match (&h2, &h3) {
+                    (Some(Heading::Date(date)), Some(section)) => (date.clone(), section.clone()),
+                    (Some(Heading::Section(section)), Some(date)) => {
+                        (parse_date_heading(date, format), section.clone())
+                    }
+                    (Some(Heading::Week), Some(section)) => {
+                        let date = task.inline_field(DATE_FIELD).unwrap_or_else(|| task_date(&task));
+                        task.remove_inline_field(DATE_FIELD);
+                        (date, section.clone())
+                    }
+                    _ => {
+                        let date = task.inline_field(DATE_FIELD).unwrap_or_else(|| task_date(&task));
+                        let section = task
+                            .inline_field(SECTION_FIELD)
+                            .unwrap_or_else(|| UNKNOWN_SECTION.to_string());
+                        task.remove_inline_field(DATE_FIELD);
+                        task.remove_inline_field(SECTION_FIELD);
+                        (date, section)
+                    }
+                };
+                log.add(&date, &section, task);
+                last_group = Some((date, section));
+            } else if !seen_entry {
+                log.header_lines.push(line.to_string());
+            } else if !line.trim().is_empty() {
+                let group = last_group.clone().or_else(|| match (&h2, &h3) {
+                    (Some(Heading::Date(date)), Some(section)) => Some((date.clone(), section.clone())),
+                    (Some(Heading::Section(section)), Some(date)) => {
+                        Some((parse_date_heading(date, format), section.clone()))
+                    }
+                    _ => None,
+                });
+                match group {
+                    Some((date, section)) => {
+                        log.group_mut(&date, &section).other_lines.push(line.to_string());
+                        last_group = Some((date, section));
+                    }
+                    None => log.header_lines.push(line.to_string()),
+                }
+            }
+        }
+
+        while log.header_lines.last().is_some_and(|l| l.trim().is_empty()) {
+            log.header_lines.pop();
+        }
+
+        log
+    }
+
+    pub fn save(&self, path: &Path, format: &DoneFormat) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.render(format))?;
+        Ok(())
+    }
+
+    pub fn render(&self, format: &DoneFormat) -> String {
+        let mut out = String::new();
+        if self.header_lines.is_empty() {
+            out.push_str(&format!("# {}\n", format.title));
+        } else {
+            for line in &self.header_lines {
+                out.push_str(&format!("{}\n", line));
+            }
+        }
+        out.push('\n');
+
+        let push_group = |out: &mut String, day: &DoneDay, section: &DoneSection, tag_date: bool| {
+            for task in &section.tasks {
+                let mut task = task.clone();
+                if tag_date && task.done_date.as_deref().unwrap_or(UNKNOWN_DATE) != day.date {
+                    task.set_inline_field(DATE_FIELD, &day.date);
+                }
+                out.push_str(&format!("{}\n", task.to_markdown()));
+            }
+            for line in &section.other_lines {
+                out.push_str(&format!("{}\n", line));
+            }
+        };
+
+        match format.group_by {
+            DoneGrouping::DateSection => {
+                for day in &self.days {
+                    out.push_str(&format!("## {}\n\n", format_date_heading(&day.date, format)));
+                    for section in &day.sections {
+                        out.push_str(&format!("### {}\n", section.name));
+                        push_group(&mut out, day, section, false);
+                        out.push('\n');
+                    }
+                }
+            }
+            DoneGrouping::SectionDate => {
+                for name in section_order(self.days.iter()) {
+                    out.push_str(&format!("## {}\n\n", name));
+                    for day in &self.days {
+                        if let Some(section) = day.sections.iter().find(|s| s.name == name) {
+                            out.push_str(&format!("### {}\n", format_date_heading(&day.date, format)));
+                            push_group(&mut out, day, section, false);
+                            out.push('\n');
+                        }
+                    }
+                }
+            }
+            DoneGrouping::Week => {
+                let mut weeks: Vec<(String, Vec<&DoneDay>)> = Vec::new();
+                for day in &self.days {
+                    let week = week_heading(&day.date);
+                    match weeks.last_mut() {
+                        Some((key, days)) if *key == week => days.push(day),
+                        _ => weeks.push((week, vec![day])),
+                    }
+                }
+                for (week, days) in &weeks {
+                    out.push_str(&format!("## {}\n\n", week));
+                    for name in section_order(days.iter().copied()) {
+                        out.push_str(&format!("### {}\n", name));
+                        for day in days {
+                            if let Some(section) = day.sections.iter().find(|s| s.name == name) {
+                                push_group(&mut out, day, section, true);
+                            }
+                        }
+                        out.push('\n');
+                    }
+                }
+            }
+            DoneGrouping::Flat => {
+                for day in &self.days {
+                    for section in &day.sections {
+                        let mut tagged = section.clone();
+                        for task in &mut tagged.tasks {
+                            task.set_inline_field(SECTION_FIELD, &section.name);
+                        }
+                        push_group(&mut out, day, &tagged, true);
+                    }
+                }
+            }
+        }
+
+        out
+    }
+
+    /// A copy containing only the tasks for which `keep(date, section, task)`
+    /// holds. Groups left empty are dropped.
+    pub fn filtered(&self, keep: impl Fn(&str, &str, &Task) -> bool) -> DoneLog {
+        let days = self
+            .days
+            .iter()
+            .map(|day| DoneDay {
+                date: day.date.clone(),
+                sections: day
+                    .sections
+                    .iter()
+                    .map(|section| DoneSection {
+                        name: section.name.clone(),
+                        tasks: section
+                            .tasks
+                            .iter()
+                            .filter(|t| keep(&day.date, &section.name, t))
+                            .cloned()
+                            .collect(),
+                        other_lines: Vec::new(),
+                    })
+                    .filter(|section| !section.tasks.is_empty())
+                    .collect(),
+            })
+            .filter(|day: &DoneDay| !day.sections.is_empty())
+            .collect();
+
+        DoneLog {
+            header_lines: self.header_lines.clone(),
+            days,
+        }
+    }
+
+    pub fn entries(&self) -> Vec<DoneEntry> {
+        self.days
+            .iter()
+            .flat_map(|day| {
+                day.sections.iter().flat_map(move |section| {
+                    section.tasks.iter().map(move |task| DoneEntry {
+                        date: day.date.clone(),
+                        section: section.name.clone(),
+                        text: task.text.clone(),
+                        done_date: task.done_date.clone(),
+                    })
+                })
+            })
+            .collect()
+    }
+
+    /// Removes the tasks numbered `nums` (1-based) from the `date` /
+    /// `section` group and returns them in order. Groups left empty are
+    /// dropped.
+    pub fn remove(
+        &mut self,
+        date: &str,
+        section: &str,
+        nums: &[usize],
+    ) -> Result<(String, Vec<Task>), Box<dyn std::error::Error>> {
+        let day_idx = self
+            .days
+            .iter()
+            .position(|d| d.date == date)
+            .ok_or(format!("Date '{}' not found in done log", date))?;
+        let day = &mut self.days[day_idx];
+        let section_idx = day
+            .sections
+            .iter()
+            .position(|s| s.name.eq_ignore_ascii_case(section))
+            .ok_or(format!("Section '{}' not found under {}", section, date))?;
+        let group = &mut day.sections[section_idx];
+
+        for num in nums {
+            if *num == 0 || *num > group.tasks.len() {
+                return Err(format!("Task {} not found in {}/{}", num, date, group.name).into());
+            }
+        }
+
+        let mut removed: Vec<Task> = nums
+            .iter()
+            .rev()
+            .map(|num| group.tasks.remove(num - 1))
+            .collect();
+        removed.reverse();
+        let name = group.name.clone();
+
+        if group.tasks.is_empty() && group.other_lines.is_empty() {
+            day.sections.remove(section_idx);
+        }
+        if day.sections.is_empty() {
+            self.days.remove(day_idx);
+        }
+
+        Ok((name, removed))
+    }
+
+    /// Moves every group of `other` into this log, keeping dates newest
+    /// first.
+    pub fn merge(&mut self, other: DoneLog) {
+        for day in other.days {
+            let idx = match self.days.iter().position(|d| d.date == day.date) {
+                Some(idx) => idx,
+                None => {
+                    let idx = self
+                        .days
+                        .iter()
+                        .position(|d| date_order(&d.date) < date_order(&day.date))
+                        .unwrap_or(self.days.len());
+                    self.days.insert(
+                        idx,
+                        DoneDay {
+                            date: day.date.clone(),
+                            sections: Vec::new(),
+                        },
+                    );
+                    idx
+                }
+            };
+
+            let target = &mut self.days[idx];
+            for section in day.sections {
+                match target.sections.iter_mut().find(|s| s.name == section.name) {
+                    Some(existing) => {
+                        existing.tasks.extend(section.tasks);
+                        existing.other_lines.extend(section.other_lines);
+                    }
+                    None => target.sections.push(section),
+                }
+            }
+        }
+    }
+
+    /// Reads the `## heading` block of a daily note as the log for `date`.
+    pub fn parse_daily_block(note: &str, heading: &str, date: &str) -> Self {
+        let mut content = format!("## {}\n", date);
+        if let Some((start, end)) = daily::block(note, heading) {
+            for line in note.lines().skip(start + 1).take(end - start - 1) {
+                content.push_str(line);
+                content.push('\n');
+            }
+        }
+
+        let mut log = Self::parse(&content);
+        log.days.retain(|day| !day.sections.is_empty());
+        log
+    }
+
+    /// `note` with its `## heading` block replaced by this log's sections.
+    /// The block is appended when missing and removed when the log is empty.
+    pub fn splice_daily_block(&self, note: &str, heading: &str) -> String {
+        let mut block = String::new();
+        if let Some(day) = self.days.first() {
+            block.push_str(&format!("## {}\n\n", heading));
+            for section in &day.sections {
+                block.push_str(&format!("### {}\n", section.name));
+                for task in &section.tasks {
+                    block.push_str(&format!("{}\n", task.to_markdown()));
+                }
+                for line in &section.other_lines {
+                    block.push_str(&format!("{}\n", line));
+                }
+                block.push('\n');
+            }
+        }
+
+        let lines: Vec<&str> = note.lines().collect();
+        let mut result = String::new();
+        match daily::block(note, heading) {
+            Some((start, end)) => {
+                for line in &lines[..start] {
+                    result.push_str(&format!("{}\n", line));
+                }
+                result.push_str(&block);
+                for line in &lines[end..] {
+                    result.push_str(&format!("{}\n", line));
+                }
+            }
+            None => {
+                result.push_str(note);
+                if !block.is_empty() {
+                    if !note.is_empty() && !note.ends_with("\n\n") {
+                        result.push_str(if note.ends_with('\n') { "\n" } else { "\n\n" });
+                    }
+                    result.push_str(&block);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Files `task` under `date` / `section`, keeping dates newest first.
+    pub fn add(&mut self, date: &str, section: &str, task: Task) {
+        self.group_mut(date, section).tasks.push(task);
+    }
+
+    /// The `date` / `section` group, created if missing.
+    fn group_mut(&mut self, date: &str, section: &str) -> &mut DoneSection {
+        let day_idx = match self.days.iter().position(|d| d.date == date) {
+            Some(idx) => idx,
+            None => {
+                let idx = self
+                    .days
+                    .iter()
+                    .position(|d| date_order(&d.date) < date_order(date))
+                    .unwrap_or(self.days.len());
+                self.days.insert(
+                    idx,
+                    DoneDay {
+                        date: date.to_string(),
+                        sections: Vec::new(),
+                    },
+                );
+                idx
+            }
+        };
+
+        let day = &mut self.days[day_idx];
+        let section_idx = match day.sections.iter().position(|s| s.name == section) {
+            Some(idx) => idx,
+            None => {
+                day.sections.push(DoneSection {
+                    name: section.to_string(),
+                    tasks: Vec::new(),
+                    other_lines: Vec::new(),
+                });
+                day.sections.len() - 1
+            }
+        };
+
+        &mut day.sections[section_idx]
+    }
+}
+
+/// Group date of tasks archived without a ✅ date.
+const UNKNOWN_DATE: &str = "unknown";
+/// Section of tasks found outside any section heading.
+const UNKNOWN_SECTION: &str = "Unknown";
+/// Inline fields carrying the group in layouts whose headings do not.
+const DATE_FIELD: &str = "date";
+const SECTION_FIELD: &str = "section";
+/// Inline field `unarchive` leaves on a task restored as done, so
+/// `archive --older-than` and `after_days` do not sweep it straight back.
+/// Dropped again when the task is archived.
+pub const UNARCHIVED_FIELD: &str = "unarchived";
+
+/// Sort key putting `YYYY-MM-DD` dates above undated groups.
+fn date_order(date: &str) -> (bool, &str) {
+    (NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok(), date)
+}
+
+/// What a `##` heading groups by.
+enum Heading {
+    Date(String),
+    Week,
+    Section(String),
+}
+
+impl Heading {
+    fn classify(text: &str, format: &DoneFormat) -> Self {
+        let is_week = Regex::new(r"^\d{4}-W\d{2}$").unwrap().is_match(text);
+        let date = parse_date_heading(text, format);
+        if is_week {
+            Heading::Week
+        } else if text == UNKNOWN_DATE || NaiveDate::parse_from_str(&date, "%Y-%m-%d").is_ok() {
+            Heading::Date(date)
+        } else {
+            Heading::Section(text.to_string())
+        }
+    }
+}
+
+/// `YYYY-MM-DD` for a heading in `date_format` (or already in
+/// `YYYY-MM-DD`); anything else is returned unchanged.
+fn parse_date_heading(text: &str, format: &DoneFormat) -> String {
+    NaiveDate::parse_from_str(text, &format.date_format)
+        .or_else(|_| NaiveDate::parse_from_str(text, "%Y-%m-%d"))
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|_| text.to_string())
+}
+
+/// `date` in `date_format`, falling back to the stored form for undated
+/// groups or an invalid format string.
+fn format_date_heading(date: &str, format: &DoneFormat) -> String {
+    let Ok(parsed) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+        return date.to_string();
+    };
+    let mut formatted = String::new();
+    match write!(formatted, "{}", parsed.format(&format.date_format)) {
+        Ok(()) => formatted,
+        Err(_) => date.to_string(),
+    }
+}
+
+fn week_heading(date: &str) -> String {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|d| d.format("%G-W%V").to_string())
+        .unwrap_or_else(|_| date.to_string())
+}
+
+/// Section names in order of first appearance.
+fn section_order<'a>(days: impl Iterator<Item = &'a DoneDay>) -> Vec<&'a str> {
+    let mut names: Vec<&str> = Vec::new();
+    for day in days {
+        for section in &day.sections {
+            if !names.contains(&section.name.as_str()) {
+                names.push(&section.name);
+            }
+        }
+    }
+    names
+}
+
+/// A file holding part of the done log under the configured `done_layout`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Location {
+    File(PathBuf),
+    /// The block under `daily_done_heading` in the note for this date.
+    DailyNote(NaiveDate),
+}
+
+/// Where the `date` group lives. Dates that are not `YYYY-MM-DD` (such as
+/// `unknown`) always stay in `done_path`.
+fn location(config: &Config, date: &str) -> Result<Location, Box<dyn std::error::Error>> {
+    let parsed = match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        Ok(parsed) => parsed,
+        Err(_) => return Ok(Location::File(config.done_path()?)),
+    };
+
+    Ok(match config.done_layout {
+        DoneLayout::Single => Location::File(config.done_path()?),
+        DoneLayout::Monthly => {
+            Location::File(config.done_dir()?.join(format!("{}.md", parsed.format("%Y-%m"))))
+        }
+        DoneLayout::Yearly => {
+            Location::File(config.done_dir()?.join(format!("{}.md", parsed.format("%Y"))))
+        }
+        DoneLayout::Daily => Location::DailyNote(parsed),
+    })
+}
+
+fn read(config: &Config, location: &Location) -> Result<DoneLog, Box<dyn std::error::Error>> {
+    match location {
+        Location::File(path) => DoneLog::load(path, &config.done_format),
+        Location::DailyNote(date) => {
+            let path = daily::note_path(config, *date)?;
+            if !path.exists() {
+                return Ok(DoneLog::default());
+            }
+            let note = fs::read_to_string(path)?;
+            let date = date.format("%Y-%m-%d").to_string();
+            Ok(DoneLog::parse_daily_block(&note, &config.daily_done_heading, &date))
+        }
+    }
+}
+
+fn write(config: &Config, location: &Location, log: &DoneLog) -> Result<(), Box<dyn std::error::Error>> {
+    match location {
+        Location::File(path) => log.save(path, &config.done_format),
+        Location::DailyNote(date) => {
+            let path = daily::note_path(config, *date)?;
+            if log.days.is_empty() && !path.exists() {
+                return Ok(());
+            }
+            let (path, _) = daily::ensure_note(config, *date)?;
+            let note = fs::read_to_string(&path)?;
+            fs::write(&path, log.splice_daily_block(&note, &config.daily_done_heading))?;
+            Ok(())
+        }
+    }
+}
+
+/// Every location that may hold entries under `layout`. `done_path` is
+/// always included for undated entries.
+fn sources(config: &Config, layout: DoneLayout) -> Result<Vec<Location>, Box<dyn std::error::Error>> {
+    let mut result = vec![Location::File(config.done_path()?)];
+
+    match layout {
+        DoneLayout::Single => {}
+        DoneLayout::Monthly | DoneLayout::Yearly => {
+            let dir = config.done_dir()?;
+            if dir.is_dir() {
+                let mut files: Vec<PathBuf> = fs::read_dir(&dir)?
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+                    .collect();
+                files.sort();
+                result.extend(files.into_iter().map(Location::File));
+            }
+        }
+        DoneLayout::Daily => {
+            for (date, _) in daily::notes(config)? {
+                result.push(Location::DailyNote(date));
+            }
+        }
+    }
+
+    result.dedup();
+    Ok(result)
+}
+
+/// The whole done log, read from every file of the configured layout.
+pub fn load_all(config: &Config) -> Result<DoneLog, Box<dyn std::error::Error>> {
+    let mut log = DoneLog::default();
+    for location in sources(config, config.done_layout)? {
+        log.merge(read(config, &location)?);
+    }
+    Ok(log)
+}
+
+/// Display name of the file the `date` group is written to.
+pub fn file_name(config: &Config, date: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let path = match location(config, date)? {
+        Location::File(path) => path,
+        Location::DailyNote(date) => daily::note_path(config, date)?,
+    };
+    Ok(path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string()))
+}
+
+/// Files each `(date, section, task)` in the done log, touching every
+/// affected file once.
+pub fn record(config: &Config, entries: Vec<(String, String, Task)>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut logs: BTreeMap<Location, DoneLog> = BTreeMap::new();
+    for (date, section, mut task) in entries {
+        task.remove_inline_field(UNARCHIVED_FIELD);
+        let location = location(config, &date)?;
+        if !logs.contains_key(&location) {
+            let log = read(config, &location)?;
+            logs.insert(location.clone(), log);
+        }
+        if let Some(log) = logs.get_mut(&location) {
+            log.add(&date, &section, task);
+        }
+    }
+
+    for (location, log) in &logs {
+        write(config, location, log)?;
+    }
+    Ok(())
+}
+
+/// Takes tasks out of the done log; see `DoneLog::remove`. Falls back to
+/// `done_path` for groups archived before the layout was changed.
+pub fn take(
+    config: &Config,
+    date: &str,
+    section: &str,
+    nums: &[usize],
+) -> Result<(String, Vec<Task>), Box<dyn std::error::Error>> {
+    let mut candidates = vec![location(config, date)?, Location::File(config.done_path()?)];
+    candidates.dedup();
+
+    for location in &candidates {
+        let mut log = read(config, location)?;
+        if log.days.iter().any(|d| d.date == date) {
+            let removed = log.remove(date, section, nums)?;
+            write(config, location, &log)?;
+            return Ok(removed);
+        }
+    }
+
+    Err(format!("Date '{}' not found in done log", date).into())
+}
+
+/// Rewrites the done log into the configured layout, collecting entries
+/// from the single file, `done_dir` and daily notes. New files are written
+/// before emptied ones are removed. Returns the number of files written.
+pub fn compact(config: &Config) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut all_sources = Vec::new();
+    for layout in [DoneLayout::Single, DoneLayout::Monthly, DoneLayout::Daily] {
+        for location in sources(config, layout)? {
+            if !all_sources.contains(&location) {
+                all_sources.push(location);
+            }
+        }
+    }
+
+    let mut merged = DoneLog::default();
+    for location in &all_sources {
+        let log = read(config, location)?;
+        if merged.header_lines.is_empty() {
+            merged.header_lines = log.header_lines.clone();
+        }
+        merged.merge(log);
+    }
+
+    let mut targets: BTreeMap<Location, DoneLog> = BTreeMap::new();
+    for day in merged.days {
+        let location = location(config, &day.date)?;
+        let log = targets.entry(location).or_insert_with(|| DoneLog {
+            header_lines: merged.header_lines.clone(),
+            days: Vec::new(),
+        });
+        log.days.push(day);
+    }
+
+    for (location, log) in &targets {
+        write(config, location, log)?;
+    }
+
+    for location in all_sources.iter().filter(|l| !targets.contains_key(l)) {
+        match location {
+            Location::File(path) => {
+                if path.exists() {
+                    fs::remove_file(path)?;
+                }
+            }
+            Location::DailyNote(_) => write(config, location, &DoneLog::default())?,
+        }
+    }
+
+    Ok(targets.len())
+}
+
+impl fmt::Display for DoneLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(&DoneFormat::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn done_task(text: &str, date: &str) -> Task {
+        Task {
+            text: text.to_string(),
+            done: true,
+            done_date: Some(date.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_add_preserves_existing_entries() {
+        let content = r#"# Done Log
+
+## 2026-02-13
+
+### Today
+- [x] Task 1 ✅ 2026-02-13
+
+## 2026-02-11
+
+### Next
+- [x] Old task ✅ 2026-02-11
+"#;
+
+        let mut log = DoneLog::parse(content);
+        log.add("2026-02-13", "Today", done_task("Task 2", "2026-02-13"));
+        log.add("2026-02-12", "Next", done_task("Middle task", "2026-02-12"));
+
+        let dates: Vec<&str> = log.days.iter().map(|d| d.date.as_str()).collect();
+        assert_eq!(dates, vec!["2026-02-13", "2026-02-12", "2026-02-11"]);
+        assert_eq!(log.days[0].sections[0].tasks.len(), 2);
+
+        let output = log.to_string();
+        assert!(output.starts_with("# Done Log\n\n## 2026-02-13\n\n### Today\n"));
+        assert!(output.contains("- [x] Old task ✅ 2026-02-11"));
+    }
+
+    #[test]
+    fn test_filtered_drops_empty_groups() {
+        let log = DoneLog::parse(
+            "# Done Log\n\n## 2026-02-13\n\n### Today\n- [x] Keep ✅ 2026-02-13\n\n### Next\n- [x] Drop ✅ 2026-02-13\n\n## 2026-02-12\n\n### Today\n- [x] Drop too ✅ 2026-02-12\n",
+        );
+
+        let filtered = log.filtered(|_, _, task| task.text == "Keep");
+        assert_eq!(filtered.days.len(), 1);
+        assert_eq!(filtered.days[0].sections.len(), 1);
+        assert_eq!(
+            filtered.entries(),
+            vec![DoneEntry {
+                date: "2026-02-13".to_string(),
+                section: "Today".to_string(),
+                text: "Keep".to_string(),
+                done_date: Some("2026-02-13".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_remove_drops_empty_groups() {
+        let mut log = DoneLog::parse(
+            "# Done Log\n\n## 2026-02-13\n\n### Today\n- [x] A ✅ 2026-02-13\n- [x] B ✅ 2026-02-13\n\n## 2026-02-12\n\n### Next\n- [x] C ✅ 2026-02-12\n",
+        );
+
+        let (name, removed) = log.remove("2026-02-13", "today", &[2]).unwrap();
+        assert_eq!(name, "Today");
+        assert_eq!(removed[0].text, "B");
+        assert_eq!(log.days[0].sections[0].tasks.len(), 1);
+
+        log.remove("2026-02-12", "Next", &[1]).unwrap();
+        assert_eq!(log.days.len(), 1);
+
+        assert!(log.remove("2026-02-13", "Today", &[5]).is_err());
+        assert!(log.remove("2026-01-01", "Today", &[1]).is_err());
+    }
+
+    #[test]
+    fn test_daily_block_round_trip() {
+        let note = "# 2026-10-17\n\n## Tasks\n- [ ] Open\n\n## Notes\nHello\n";
+        let log = DoneLog::parse_daily_block(note, "Done", "2026-10-17");
+        assert!(log.days.is_empty());
+
+        let mut log = log;
+        log.add("2026-10-17", "Today", done_task("Shipped", "2026-10-17"));
+        let with_block = log.splice_daily_block(note, "Done");
+        assert_eq!(
+            with_block,
+            "# 2026-10-17\n\n## Tasks\n- [ ] Open\n\n## Notes\nHello\n\n## Done\n\n### Today\n- [x] Shipped ✅ 2026-10-17\n\n"
+        );
+
+        let parsed = DoneLog::parse_daily_block(&with_block, "Done", "2026-10-17");
+        assert_eq!(parsed.entries(), log.entries());
+
+        assert_eq!(DoneLog::default().splice_daily_block(&with_block, "Done").trim_end(), note.trim_end());
+    }
+
+    fn sample_log() -> DoneLog {
+        let mut log = DoneLog::default();
+        log.add("2026-10-13", "Next", done_task("C", "2026-10-13"));
+        log.add("2026-10-17", "Today", done_task("A", "2026-10-17"));
+        log.add("2026-10-17", "Next", done_task("B", "2026-10-17"));
+        log.add("unknown", "Today", Task {
+            text: "Undated".to_string(),
+            done: true,
+            done_date: None,
+        });
+        log
+    }
+
+    #[test]
+    fn test_every_grouping_reads_back() {
+        let log = sample_log();
+        for group_by in [
+            DoneGrouping::DateSection,
+            DoneGrouping::SectionDate,
+            DoneGrouping::Week,
+            DoneGrouping::Flat,
+        ] {
+            let format = DoneFormat {
+                group_by,
+                date_format: "%d.%m.%Y".to_string(),
+                ..DoneFormat::default()
+            };
+            let rendered = log.render(&format);
+            let parsed = DoneLog::parse_with(&rendered, &format);
+
+            let mut expected = log.entries();
+            let mut actual = parsed.entries();
+            expected.sort_by(|a, b| (&a.date, &a.section, &a.text).cmp(&(&b.date, &b.section, &b.text)));
+            actual.sort_by(|a, b| (&a.date, &a.section, &a.text).cmp(&(&b.date, &b.section, &b.text)));
+            assert_eq!(actual, expected, "{:?}:\n{}", group_by, rendered);
+        }
+    }
+
+    #[test]
+    fn test_grouping_output() {
+        let log = sample_log();
+        let format = |group_by| DoneFormat {
+            group_by,
+            title: "Archive".to_string(),
+            ..DoneFormat::default()
+        };
+
+        assert_eq!(
+            log.render(&format(DoneGrouping::SectionDate)),
+            "# Archive\n\n## Today\n\n### 2026-10-17\n- [x] A ✅ 2026-10-17\n\n### unknown\n- [x] Undated\n\n## Next\n\n### 2026-10-17\n- [x] B ✅ 2026-10-17\n\n### 2026-10-13\n- [x] C ✅ 2026-10-13\n\n"
+        );
+        assert_eq!(
+            log.render(&format(DoneGrouping::Week)),
+            "# Archive\n\n## 2026-W42\n\n### Today\n- [x] A ✅ 2026-10-17\n\n### Next\n- [x] B ✅ 2026-10-17\n- [x] C ✅ 2026-10-13\n\n## unknown\n\n### Today\n- [x] Undated\n\n"
+        );
+        assert_eq!(
+            log.render(&format(DoneGrouping::Flat)),
+            "# Archive\n\n- [x] A [section:: Today] ✅ 2026-10-17\n- [x] B [section:: Next] ✅ 2026-10-17\n- [x] C [section:: Next] ✅ 2026-10-13\n- [x] Undated [section:: Today]\n"
+        );
+    }
+
+    #[test]
+    fn test_empty_log_gets_title() {
+        let mut log = DoneLog::parse("");
+        log.add("2026-02-13", "Today", done_task("Task", "2026-02-13"));
+        assert_eq!(
+            log.to_string(),
+            "# Done Log\n\n## 2026-02-13\n\n### Today\n- [x] Task ✅ 2026-02-13\n\n"
+        );
+    }
+}
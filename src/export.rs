@@ -0,0 +1,32 @@
+use crate::ics;
+use crate::markdown::TodoFile;
+use crate::records;
+use crate::todotxt;
+use chrono::{DateTime, Utc};
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// iCalendar VTODO entries
+    Ics,
+    /// todo.txt, one task per line
+    Todotxt,
+    /// One CSV row per task with section, position, status and metadata
+    Csv,
+    /// The same rows as a JSON array
+    Json,
+}
+
+/// Renders `files` (`(prefix, todo)` pairs from `vault::todo_files`) in
+/// `format`.
+pub fn export(
+    files: &[(String, TodoFile)],
+    format: ExportFormat,
+    now: DateTime<Utc>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    match format {
+        ExportFormat::Ics => Ok(ics::calendar(files, now)),
+        ExportFormat::Todotxt => Ok(todotxt::export(files)),
+        ExportFormat::Csv => records::to_csv(&records::records(files)),
+        ExportFormat::Json => records::to_json(&records::records(files)),
+    }
+}
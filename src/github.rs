@@ -0,0 +1,143 @@
+use crate::config::Config;
+use crate::import::{ImportedTask, SOURCE};
+use crate::markdown::{Task, CREATED, DUE};
+use chrono::{DateTime, NaiveDate};
+use serde::Deserialize;
+
+/// One issue from `gh issue list --json ...` (camelCase) or the REST API
+/// (snake_case).
+#[derive(Deserialize, Debug)]
+struct Issue {
+    number: u64,
+    title: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    labels: Vec<Label>,
+    milestone: Option<Milestone>,
+    #[serde(alias = "html_url")]
+    url: Option<String>,
+    #[serde(rename = "createdAt", alias = "created_at")]
+    created_at: Option<String>,
+    #[serde(rename = "closedAt", alias = "closed_at")]
+    closed_at: Option<String>,
+    /// Present on pull requests in REST API dumps.
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Label {
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Milestone {
+    title: String,
+    #[serde(rename = "dueOn", alias = "due_on")]
+    due_on: Option<String>,
+}
+
+/// Tasks for the issues in a GitHub JSON dump. The milestone becomes the
+/// section and its due date 📅, labels become `#tags` (spaces as `-`),
+/// closed issues are done, and `owner/repo#N` is kept as
+/// `[source:: github:owner/repo#N]`. Pull requests are skipped.
+pub fn parse(config: &Config, content: &str) -> Result<Vec<ImportedTask>, Box<dyn std::error::Error>> {
+    let issues: Vec<Issue> = serde_json::from_str(content)?;
+    let local_date = |value: Option<&str>| -> Result<Option<NaiveDate>, Box<dyn std::error::Error>> {
+        match value.and_then(|v| DateTime::parse_from_rfc3339(v).ok()) {
+            Some(dt) => Ok(Some(config.local_date(dt.to_utc())?)),
+            None => Ok(None),
+        }
+    };
+    let mut tasks = Vec::new();
+
+    for issue in issues {
+        if issue.pull_request.is_some() {
+            continue;
+        }
+
+        let mut text = issue.title.trim().to_string();
+        for label in &issue.labels {
+            text.push_str(&format!(" #{}", label.name.trim().replace(' ', "-")));
+        }
+        let done = issue.state.eq_ignore_ascii_case("closed");
+        let mut task = Task {
+            text,
+            done,
+            done_date: if done { local_date(issue.closed_at.as_deref())?.map(|d| d.to_string()) } else { None },
+        };
+
+        if let Some(created) = local_date(issue.created_at.as_deref())? {
+            task.set_field(CREATED, &created.to_string());
+        }
+        let due = local_date(issue.milestone.as_ref().and_then(|m| m.due_on.as_deref()))?;
+        if let Some(due) = due {
+            task.set_field(DUE, &due.to_string());
+        }
+        task.set_inline_field(SOURCE, &format!("github:{}", reference(&issue)));
+
+        tasks.push(ImportedTask::new(issue.milestone.map(|m| m.title), task));
+    }
+
+    Ok(tasks)
+}
+
+/// `owner/repo#N` from the issue URL, or `#N` without one.
+fn reference(issue: &Issue) -> String {
+    let repo = issue.url.as_deref().and_then(|url| {
+        let path = url.strip_prefix("https://github.com/")?;
+        let mut parts = path.split('/');
+        Some(format!("{}/{}", parts.next()?, parts.next()?))
+    });
+    format!("{}#{}", repo.unwrap_or_default(), issue.number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc() -> Config {
+        Config {
+            timezone: "UTC".to_string(),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_gh_cli() {
+        let json = r#"[
+            {"number": 12, "title": "Crash on empty file", "state": "OPEN",
+             "labels": [{"name": "bug"}, {"name": "good first issue"}],
+             "milestone": {"title": "v1.2", "dueOn": "2026-11-01T12:00:00Z"},
+             "url": "https://github.com/acme/mdtodo/issues/12", "createdAt": "2026-10-02T12:00:00Z"},
+            {"number": 9, "title": "Docs typo", "state": "CLOSED", "labels": [], "milestone": null,
+             "url": "https://github.com/acme/mdtodo/issues/9", "closedAt": "2026-10-10T12:00:00Z"}
+        ]"#;
+        let tasks = parse(&utc(), json).unwrap();
+
+        assert_eq!(tasks[0].section.as_deref(), Some("v1.2"));
+        assert_eq!(
+            tasks[0].task.to_markdown(),
+            "- [ ] Crash on empty file #bug #good-first-issue ➕ 2026-10-02 📅 2026-11-01 [source:: github:acme/mdtodo#12]"
+        );
+        assert_eq!(tasks[1].section, None);
+        assert_eq!(
+            tasks[1].task.to_markdown(),
+            "- [x] Docs typo [source:: github:acme/mdtodo#9] ✅ 2026-10-10"
+        );
+    }
+
+    #[test]
+    fn test_parse_rest_api() {
+        let json = r#"[
+            {"number": 3, "title": "Add export", "state": "open", "labels": [{"name": "feature"}],
+             "milestone": {"title": "Next", "due_on": null}, "html_url": "https://github.com/acme/mdtodo/issues/3",
+             "created_at": "2026-10-05T12:00:00Z", "closed_at": null},
+            {"number": 4, "title": "A pull request", "state": "open", "pull_request": {"url": "x"}}
+        ]"#;
+        let tasks = parse(&utc(), json).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].section.as_deref(), Some("Next"));
+        assert_eq!(tasks[0].task.text, "Add export #feature ➕ 2026-10-05 [source:: github:acme/mdtodo#3]");
+    }
+}
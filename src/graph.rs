@@ -0,0 +1,233 @@
+use crate::deps;
+use crate::markdown::{TodoFile, DEPENDS_ON, ID};
+use std::collections::HashMap;
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    /// Section, with the vault file prefix when there is one.
+    pub section: String,
+    /// Task text without the 🆔 / ⛔ fields.
+    pub text: String,
+    pub done: bool,
+}
+
+/// Dependency graph. Edges run from a blocker to the task waiting on it.
+#[derive(Debug, Default)]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl Graph {
+    /// Open tasks with dependency links, plus the completed tasks they are
+    /// linked to. `files` are `(prefix, todo)` pairs as returned by
+    /// `vault::todo_files`.
+    pub fn build(files: &[(String, TodoFile)]) -> Self {
+        let mut all = Graph::default();
+        let mut by_id: HashMap<String, usize> = HashMap::new();
+        let mut blockers: Vec<Vec<String>> = Vec::new();
+
+        for (prefix, todo) in files {
+            for section in &todo.sections {
+                for task in &section.tasks {
+                    let id = task.field(ID);
+                    let ids = deps::blocker_ids(task);
+                    if id.is_none() && ids.is_empty() {
+                        continue;
+                    }
+
+                    let mut text = task.clone();
+                    text.remove_field(ID);
+                    text.remove_field(DEPENDS_ON);
+                    if let Some(id) = id {
+                        by_id.entry(id).or_insert(all.nodes.len());
+                    }
+                    all.nodes.push(Node {
+                        section: format!("{}{}", prefix, section.name),
+                        text: text.text,
+                        done: task.done,
+                    });
+                    blockers.push(ids);
+                }
+            }
+        }
+
+        for (node, ids) in blockers.iter().enumerate() {
+            for id in ids {
+                if let Some(&blocker) = by_id.get(id) {
+                    all.edges.push((blocker, node));
+                }
+            }
+        }
+
+        // Keep open tasks, and done ones only where they touch an open task.
+        let keep: Vec<bool> = (0..all.nodes.len())
+            .map(|i| {
+                let linked = all.edges.iter().any(|&(a, b)| a == i || b == i);
+                let touches_open = all.edges.iter().any(|&(a, b)| {
+                    (a == i && !all.nodes[b].done) || (b == i && !all.nodes[a].done)
+                });
+                (!all.nodes[i].done && linked) || touches_open
+            })
+            .collect();
+
+        let mut index = vec![None; all.nodes.len()];
+        let mut graph = Graph::default();
+        for (i, node) in all.nodes.into_iter().enumerate() {
+            if keep[i] {
+                index[i] = Some(graph.nodes.len());
+                graph.nodes.push(node);
+            }
+        }
+        for (a, b) in all.edges {
+            if let (Some(a), Some(b)) = (index[a], index[b]) {
+                graph.edges.push((a, b));
+            }
+        }
+
+        graph
+    }
+
+    /// A dependency cycle as a list of nodes, first node repeated at the end.
+    pub fn find_cycle(&self) -> Option<Vec<usize>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            New,
+            Active,
+            Finished,
+        }
+
+        fn visit(graph: &Graph, node: usize, marks: &mut [Mark], path: &mut Vec<usize>) -> Option<Vec<usize>> {
+            marks[node] = Mark::Active;
+            path.push(node);
+            for &(_, next) in graph.edges.iter().filter(|(from, _)| *from == node) {
+                match marks[next] {
+                    Mark::Active => {
+                        let start = path.iter().position(|&n| n == next).unwrap_or(0);
+                        let mut cycle = path[start..].to_vec();
+                        cycle.push(next);
+                        return Some(cycle);
+                    }
+                    Mark::New => {
+                        if let Some(cycle) = visit(graph, next, marks, path) {
+                            return Some(cycle);
+                        }
+                    }
+                    Mark::Finished => {}
+                }
+            }
+            path.pop();
+            marks[node] = Mark::Finished;
+            None
+        }
+
+        let mut marks = vec![Mark::New; self.nodes.len()];
+        for node in 0..self.nodes.len() {
+            if marks[node] == Mark::New {
+                if let Some(cycle) = visit(self, node, &mut marks, &mut Vec::new()) {
+                    return Some(cycle);
+                }
+            }
+        }
+        None
+    }
+
+    fn label(&self, node: usize) -> String {
+        let node = &self.nodes[node];
+        format!("{}: {}", node.section, node.text)
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph mdtodo {\n    rankdir=LR;\n    node [shape=box];\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let label = self.label(i).replace('\\', "\\\\").replace('"', "\\\"");
+            let style = if node.done { ", color=gray, fontcolor=gray" } else { "" };
+            out.push_str(&format!("    t{} [label=\"{}\"{}];\n", i, label, style));
+        }
+        for (a, b) in &self.edges {
+            out.push_str(&format!("    t{} -> t{};\n", a, b));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("graph LR\n");
+        for i in 0..self.nodes.len() {
+            let label = self.label(i).replace('"', "#quot;");
+            out.push_str(&format!("    t{}[\"{}\"]\n", i, label));
+        }
+        for (a, b) in &self.edges {
+            out.push_str(&format!("    t{} --> t{}\n", a, b));
+        }
+        let done: Vec<String> = (0..self.nodes.len())
+            .filter(|&i| self.nodes[i].done)
+            .map(|i| format!("t{}", i))
+            .collect();
+        if !done.is_empty() {
+            out.push_str("    classDef done fill:#eee,stroke:#bbb,color:#999\n");
+            out.push_str(&format!("    class {} done\n", done.join(",")));
+        }
+        out
+    }
+}
+
+/// The graph in `format`, or an error naming the tasks of a cycle.
+pub fn render(files: &[(String, TodoFile)], format: GraphFormat) -> Result<String, Box<dyn std::error::Error>> {
+    let graph = Graph::build(files);
+    if let Some(cycle) = graph.find_cycle() {
+        let path: Vec<String> = cycle.iter().map(|&n| graph.label(n)).collect();
+        return Err(format!("Dependency cycle: {}", path.join(" -> ")).into());
+    }
+
+    Ok(match format {
+        GraphFormat::Dot => graph.to_dot(),
+        GraphFormat::Mermaid => graph.to_mermaid(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(content: &str) -> Vec<(String, TodoFile)> {
+        vec![(String::new(), TodoFile::parse(content).unwrap())]
+    }
+
+    #[test]
+    fn test_build_keeps_open_tasks_and_linked_done_ones() {
+        let files = files(
+            "## Today\n- [ ] Implement ⛔ spec01,room01\n- [ ] Unrelated\n\n## Next\n- [ ] Write \"spec\" 🆔 spec01\n- [x] Book room 🆔 room01 ✅ 2026-10-17\n- [x] Old 🆔 old001 ✅ 2026-10-01\n- [x] Older ⛔ old001 ✅ 2026-10-02\n",
+        );
+        let graph = Graph::build(&files);
+
+        let labels: Vec<String> = (0..graph.nodes.len()).map(|i| graph.label(i)).collect();
+        assert_eq!(labels, vec!["Today: Implement", "Next: Write \"spec\"", "Next: Book room"]);
+        assert_eq!(graph.edges, vec![(1, 0), (2, 0)]);
+        assert!(graph.find_cycle().is_none());
+
+        let dot = render(&files, GraphFormat::Dot).unwrap();
+        assert!(dot.contains("t1 [label=\"Next: Write \\\"spec\\\"\"];"));
+        assert!(dot.contains("t2 [label=\"Next: Book room\", color=gray, fontcolor=gray];"));
+        assert!(dot.contains("t1 -> t0;"));
+
+        let mermaid = render(&files, GraphFormat::Mermaid).unwrap();
+        assert!(mermaid.starts_with("graph LR\n    t0[\"Today: Implement\"]\n"));
+        assert!(mermaid.contains("t1[\"Next: Write #quot;spec#quot;\"]"));
+        assert!(mermaid.contains("    t2 --> t0\n"));
+        assert!(mermaid.ends_with("    class t2 done\n"));
+    }
+
+    #[test]
+    fn test_cycle_is_an_error() {
+        let files = files("## Today\n- [ ] A 🆔 aaa ⛔ ccc\n- [ ] B 🆔 bbb ⛔ aaa\n- [ ] C 🆔 ccc ⛔ bbb\n");
+        let err = render(&files, GraphFormat::Dot).unwrap_err().to_string();
+        assert_eq!(err, "Dependency cycle: Today: A -> Today: B -> Today: C -> Today: A");
+    }
+}
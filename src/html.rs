@@ -0,0 +1,155 @@
+use crate::done_log::DoneLog;
+use crate::markdown::{TodoFile, DUE, PRIORITIES};
+use chrono::NaiveDate;
+use std::fmt::Write;
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum RenderFormat {
+    /// A single self-contained HTML page
+    Html,
+}
+
+const STYLE: &str = "\
+body{font-family:system-ui,sans-serif;max-width:60rem;margin:2rem auto;padding:0 1rem;color:#222}
+h1{margin-bottom:.2rem}
+.generated{color:#777;font-size:.85rem}
+section{margin:1.5rem 0}
+h2{border-bottom:1px solid #ddd;padding-bottom:.2rem}
+ul{list-style:none;padding-left:0}
+li{margin:.3rem 0}
+li.done .text{text-decoration:line-through;color:#888}
+.badge{display:inline-block;font-size:.75rem;padding:.05rem .45rem;border-radius:.6rem;margin-left:.4rem;background:#eef}
+.badge.today{background:#ffe8a3}
+.badge.overdue{background:#d33;color:#fff}
+li.overdue .text{color:#b00}
+.timeline ol{list-style:none;padding-left:1rem;border-left:2px solid #ddd}
+.timeline time{font-weight:bold}
+.timeline .section{color:#777;font-size:.85rem;margin-right:.4rem}
+";
+
+/// The board as one HTML page with inline CSS: every section of `files`
+/// (`(prefix, todo)` pairs from `vault::todo_files`) with checkboxes, due
+/// badges and overdue tasks highlighted relative to `today`, followed by
+/// the done log as a timeline, newest day first.
+pub fn page(files: &[(String, TodoFile)], done_log: &DoneLog, today: NaiveDate) -> String {
+    let title = files
+        .iter()
+        .flat_map(|(_, todo)| &todo.header_lines)
+        .find_map(|line| line.strip_prefix("# "))
+        .unwrap_or("TODO")
+        .trim();
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    let _ = writeln!(html, "<title>{}</title>", escape(title));
+    let _ = writeln!(html, "<style>\n{}</style>\n</head>\n<body>", STYLE);
+    let _ = writeln!(html, "<h1>{}</h1>", escape(title));
+    let _ = writeln!(html, "<p class=\"generated\">Generated {}</p>", today);
+
+    for (prefix, todo) in files {
+        for section in &todo.sections {
+            let _ = writeln!(html, "<section>\n<h2>{}{}</h2>\n<ul>", escape(prefix), escape(&section.name));
+            for task in &section.tasks {
+                let due = task.date_field(DUE);
+                let overdue = !task.done && due.is_some_and(|d| d < today);
+                let mut classes = Vec::new();
+                if task.done {
+                    classes.push("done");
+                }
+                if overdue {
+                    classes.push("overdue");
+                }
+
+                let _ = write!(
+                    html,
+                    "<li class=\"{}\"><input type=\"checkbox\" disabled{}> <span class=\"text\">{}</span>",
+                    classes.join(" "),
+                    if task.done { " checked" } else { "" },
+                    escape(&task.summary())
+                );
+                if let Some(priority) = task.priority() {
+                    let _ = write!(html, "<span class=\"badge priority\">{}</span>", PRIORITIES[priority]);
+                }
+                if let Some(due) = due {
+                    let class = if overdue {
+                        " overdue"
+                    } else if !task.done && due == today {
+                        " today"
+                    } else {
+                        ""
+                    };
+                    let _ = write!(html, "<span class=\"badge due{}\">📅 {}</span>", class, due);
+                }
+                html.push_str("</li>\n");
+            }
+            html.push_str("</ul>\n</section>\n");
+        }
+    }
+
+    if !done_log.days.is_empty() {
+        html.push_str("<section class=\"timeline\">\n<h2>Done</h2>\n<ol>\n");
+        let mut days: Vec<_> = done_log.days.iter().collect();
+        // Newest first; groups without a parseable date go last.
+        days.sort_by_key(|day| {
+            let date = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok();
+            (date.is_none(), std::cmp::Reverse(date))
+        });
+        for day in days {
+            let _ = writeln!(html, "<li><time>{}</time>\n<ul>", escape(&day.date));
+            for section in &day.sections {
+                for task in &section.tasks {
+                    let _ = writeln!(
+                        html,
+                        "<li><span class=\"section\">{}</span>{}</li>",
+                        escape(&section.name),
+                        escape(&task.summary())
+                    );
+                }
+            }
+            html.push_str("</ul></li>\n");
+        }
+        html.push_str("</ol>\n</section>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page() {
+        let todo = TodoFile::parse(
+            "# Team board\n\n## Today\n- [ ] Fix <script> tag 📅 2026-10-17 ⏫\n- [ ] Standup 📅 2026-10-18\n- [x] Ship 📅 2026-10-01 ✅ 2026-10-02\n",
+        )
+        .unwrap();
+        let done_log = DoneLog::parse(
+            "# Done Log\n\n## 2026-10-16\n### Today\n- [x] Old thing ✅ 2026-10-16\n\n## 2026-10-17\n### Next\n- [x] Newer thing ✅ 2026-10-17\n",
+        );
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let html = page(&[(String::new(), todo)], &done_log, today);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Team board</title>"));
+        assert!(html.contains(
+            "<li class=\"overdue\"><input type=\"checkbox\" disabled> <span class=\"text\">Fix &lt;script&gt; tag</span><span class=\"badge priority\">⏫</span><span class=\"badge due overdue\">📅 2026-10-17</span></li>"
+        ));
+        assert!(html.contains("<span class=\"badge due today\">📅 2026-10-18</span>"));
+        assert!(html.contains("<li class=\"done\"><input type=\"checkbox\" disabled checked> <span class=\"text\">Ship</span><span class=\"badge due\">📅 2026-10-01</span>"));
+
+        let newer = html.find("<time>2026-10-17</time>").unwrap();
+        let older = html.find("<time>2026-10-16</time>").unwrap();
+        assert!(newer < older);
+        assert!(html.contains("<span class=\"section\">Next</span>Newer thing</li>"));
+    }
+}
@@ -0,0 +1,164 @@
+use crate::markdown::{TodoFile, CREATED, DUE, ID, SCHEDULED, START};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashSet;
+
+/// RFC 5545 limits content lines to 75 octets before folding.
+const MAX_LINE: usize = 75;
+
+/// A VCALENDAR with one VTODO per task that has a due, scheduled or start
+/// date. `files` are `(prefix, todo)` pairs as returned by
+/// `vault::todo_files`.
+pub fn calendar(files: &[(String, TodoFile)], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//mdtodo//mdtodo//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:mdtodo".to_string(),
+    ];
+    let dtstamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut uids = HashSet::new();
+
+    for (_, todo) in files {
+        for section in &todo.sections {
+            for task in &section.tasks {
+                let due = task.date_field(DUE);
+                let start = task.date_field(SCHEDULED).or_else(|| task.date_field(START));
+                if due.is_none() && start.is_none() {
+                    continue;
+                }
+
+                let summary = task.summary();
+                let mut uid = match task.field(ID) {
+                    Some(id) => format!("{}@mdtodo", id),
+                    None => format!("{:016x}@mdtodo", stable_hash(&summary)),
+                };
+                let base = uid.clone();
+                let mut n = 2;
+                while !uids.insert(uid.clone()) {
+                    uid = format!("{}-{}", base, n);
+                    n += 1;
+                }
+
+                lines.push("BEGIN:VTODO".to_string());
+                lines.push(format!("UID:{}", uid));
+                lines.push(format!("DTSTAMP:{}", dtstamp));
+                lines.push(format!("SUMMARY:{}", escape_text(&summary)));
+                if let Some(created) = task.date_field(CREATED) {
+                    lines.push(format!("CREATED:{}", utc_midnight(created)));
+                }
+                if let Some(start) = start {
+                    lines.push(format!("DTSTART;VALUE=DATE:{}", start.format("%Y%m%d")));
+                }
+                if let Some(due) = due {
+                    lines.push(format!("DUE;VALUE=DATE:{}", due.format("%Y%m%d")));
+                }
+                if let Some(priority) = task.priority() {
+                    lines.push(format!("PRIORITY:{}", ics_priority(priority)));
+                }
+                if task.done {
+                    lines.push("STATUS:COMPLETED".to_string());
+                    let completed = task
+                        .done_date
+                        .as_deref()
+                        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+                    if let Some(completed) = completed {
+                        lines.push(format!("COMPLETED:{}", utc_midnight(completed)));
+                    }
+                } else {
+                    lines.push("STATUS:NEEDS-ACTION".to_string());
+                }
+
+                let categories: Vec<String> = std::iter::once(section.name.clone())
+                    .chain(task.tags())
+                    .map(|c| escape_text(&c))
+                    .collect();
+                lines.push(format!("CATEGORIES:{}", categories.join(",")));
+                lines.push("END:VTODO".to_string());
+            }
+        }
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// Obsidian Tasks priorities (highest first) on the iCalendar 1–9 scale.
+fn ics_priority(priority: usize) -> u8 {
+    [1, 3, 5, 7, 9][priority.min(4)]
+}
+
+fn utc_midnight(date: NaiveDate) -> String {
+    format!("{}T000000Z", date.format("%Y%m%d"))
+}
+
+/// FNV-1a, so UIDs stay the same across runs and Rust versions.
+fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// The line with CRLF endings, folded at 75 octets without splitting a
+/// UTF-8 character.
+fn fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_calendar() {
+        let todo = TodoFile::parse(
+            "## Today\n- [ ] Pay rent, then relax #home ⏫ 📅 2026-11-01 ⏳ 2026-10-30\n- [ ] No dates\n\n## Next\n- [x] Ship release 🆔 rel001 📅 2026-10-15 ✅ 2026-10-14\n",
+        )
+        .unwrap();
+        let now = Utc.with_ymd_and_hms(2026, 10, 18, 9, 30, 0).unwrap();
+        let ics = calendar(&[(String::new(), todo)], now);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VTODO").count(), 2);
+        assert!(ics.contains("SUMMARY:Pay rent\\, then relax #home\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20261030\r\nDUE;VALUE=DATE:20261101\r\nPRIORITY:3\r\nSTATUS:NEEDS-ACTION\r\nCATEGORIES:Today,home\r\n"));
+        assert!(ics.contains("UID:rel001@mdtodo\r\nDTSTAMP:20261018T093000Z\r\nSUMMARY:Ship release\r\n"));
+        assert!(ics.contains("STATUS:COMPLETED\r\nCOMPLETED:20261014T000000Z\r\n"));
+
+        let again = calendar(
+            &[(String::new(), TodoFile::parse("## Later\n- [ ] Pay rent, then relax #home 📅 2026-12-01\n").unwrap())],
+            now,
+        );
+        let uid = |ics: &str| ics.lines().find(|l| l.starts_with("UID:")).unwrap().to_string();
+        assert_eq!(uid(&ics), uid(&again));
+    }
+
+    #[test]
+    fn test_fold() {
+        let line = format!("SUMMARY:{}", "あ".repeat(30));
+        let folded = fold(&line);
+        let parts: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+        assert!(parts.iter().all(|p| p.len() <= MAX_LINE));
+        assert_eq!(parts.concat().replace(" あ", "あ"), line);
+    }
+}
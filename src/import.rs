@@ -0,0 +1,257 @@
+use crate::config::Config;
+use crate::markdown::{Task, TodoFile, CREATED, DEPENDS_ON, DUE, ID, SCHEDULED, START};
+use crate::github;
+use crate::records;
+use crate::taskwarrior;
+use crate::todotxt;
+use regex::Regex;
+
+/// Inline field recording where an imported task came from, e.g.
+/// `[source:: github:acme/mdtodo#12]`.
+pub const SOURCE: &str = "source";
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum ImportFormat {
+    /// todo.txt, one task per line
+    Todotxt,
+    /// CSV as written by `export --format csv`
+    Csv,
+    /// JSON as written by `export --format json`
+    Json,
+    /// Taskwarrior `task export` JSON
+    Taskwarrior,
+    /// GitHub issues JSON from `gh issue list --json` or the REST API
+    Github,
+}
+
+/// A task read from an import file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedTask {
+    /// The section the source put it in, when it records one.
+    pub section: Option<String>,
+    /// 1-based position within that section, when the source records one.
+    pub position: Option<usize>,
+    pub task: Task,
+}
+
+impl ImportedTask {
+    pub fn new(section: Option<String>, task: Task) -> Self {
+        ImportedTask {
+            section,
+            position: None,
+            task,
+        }
+    }
+}
+
+/// Reads `content` in `format`. Timestamps in the Taskwarrior and GitHub
+/// formats are turned into dates in `config.timezone`.
+pub fn parse(
+    config: &Config,
+    content: &str,
+    format: ImportFormat,
+) -> Result<Vec<ImportedTask>, Box<dyn std::error::Error>> {
+    let rows = match format {
+        ImportFormat::Todotxt => {
+            return Ok(todotxt::parse(content)
+                .into_iter()
+                .map(|(section, task)| ImportedTask::new(section, task))
+                .collect())
+        }
+        ImportFormat::Taskwarrior => return taskwarrior::parse(config, content),
+        ImportFormat::Github => return github::parse(config, content),
+        ImportFormat::Csv => records::from_csv(content)?,
+        ImportFormat::Json => records::from_json(content)?,
+    };
+    records::sorted(rows)
+        .into_iter()
+        .map(|row| {
+            let section = Some(row.section.trim().to_string()).filter(|s| !s.is_empty());
+            Ok(ImportedTask {
+                position: row.position.filter(|_| section.is_some()),
+                section,
+                task: row.to_task()?,
+            })
+        })
+        .collect()
+}
+
+/// What identifies a task across imports: its `[source:: ...]`, else its
+/// 🆔, else its text without metadata.
+pub fn merge_key(task: &Task) -> String {
+    if let Some(source) = task.inline_field(SOURCE) {
+        return format!("{}:: {}", SOURCE, source);
+    }
+    match task.field(ID) {
+        Some(id) => format!("{} {}", ID, id),
+        None => task.summary(),
+    }
+}
+
+/// The imported task with whatever it does not say taken from the task it
+/// updates: 📅 / ⏳ / 🆔 / ⛔ and the other emoji fields, the priority and
+/// inline fields such as `[carried:: 2]` stay unless the import sets them.
+pub fn merge(existing: &Task, imported: Task) -> Task {
+    let mut task = imported;
+    for emoji in [CREATED, START, SCHEDULED, DUE, ID, DEPENDS_ON] {
+        if let (None, Some(value)) = (task.field(emoji), existing.field(emoji)) {
+            task.set_field(emoji, &value);
+        }
+    }
+    if task.priority().is_none() {
+        task.set_priority(existing.priority());
+    }
+    let inline = Regex::new(r"\[([^\]:]+)::([^\]]*)\]").unwrap();
+    for caps in inline.captures_iter(&existing.text) {
+        let key = caps[1].trim();
+        if task.inline_field(key).is_none() {
+            task.set_inline_field(key, caps[2].trim());
+        }
+    }
+    task
+}
+
+/// Adds `tasks` to `todo`, filing those without a section under
+/// `fallback`. An existing task with the same `merge_key` is updated in
+/// place (see `merge`), or moved when the import files it under another
+/// section; tasks without a section stay wherever they were filed. Rows
+/// that record a section and position are matched to the task at that
+/// spot first, and each task is matched at most once, so repeated texts
+/// do not collapse onto one task. With `replace`, every existing task is
+/// dropped first; section headings and their notes stay. Returns the
+/// number of tasks added and updated.
+pub fn apply(todo: &mut TodoFile, tasks: Vec<ImportedTask>, fallback: &str, replace: bool) -> (usize, usize) {
+    if replace {
+        for section in &mut todo.sections {
+            section.tasks.clear();
+        }
+    }
+
+    let mut added = 0;
+    let mut updated = 0;
+    // (section, task) indices already written by this import.
+    let mut claimed: Vec<(usize, usize)> = Vec::new();
+    for imported in tasks {
+        let existing = find_existing(todo, &imported, &claimed);
+        let ImportedTask { section, task, .. } = imported;
+        let target = match (&section, existing) {
+            (None, Some((s, _))) => s,
+            _ => todo.get_or_create_section(section.as_deref().unwrap_or(fallback)),
+        };
+        match existing {
+            Some((s, i)) if s == target => {
+                todo.sections[s].tasks[i] = merge(&todo.sections[s].tasks[i], task);
+                claimed.push((s, i));
+                updated += 1;
+            }
+            Some((s, i)) => {
+                let existing = todo.sections[s].tasks.remove(i);
+                for claim in claimed.iter_mut().filter(|(cs, ci)| *cs == s && *ci > i) {
+                    claim.1 -= 1;
+                }
+                todo.sections[target].tasks.push(merge(&existing, task));
+                claimed.push((target, todo.sections[target].tasks.len() - 1));
+                updated += 1;
+            }
+            None => {
+                todo.sections[target].tasks.push(task);
+                claimed.push((target, todo.sections[target].tasks.len() - 1));
+                added += 1;
+            }
+        }
+    }
+    (added, updated)
+}
+
+/// The unclaimed task `imported` updates: the one at its recorded section
+/// and position when the key matches there, else the first with its key,
+/// looking in its own section before the others.
+fn find_existing(todo: &TodoFile, imported: &ImportedTask, claimed: &[(usize, usize)]) -> Option<(usize, usize)> {
+    let key = merge_key(&imported.task);
+    let matches = |s: usize, i: usize| !claimed.contains(&(s, i)) && merge_key(&todo.sections[s].tasks[i]) == key;
+    let own = imported.section.as_deref().and_then(|name| todo.find_section(name));
+
+    if let (Some(s), Some(position)) = (own, imported.position) {
+        if position >= 1 && position <= todo.sections[s].tasks.len() && matches(s, position - 1) {
+            return Some((s, position - 1));
+        }
+    }
+    own.into_iter()
+        .chain(0..todo.sections.len())
+        .find_map(|s| (0..todo.sections[s].tasks.len()).find(|&i| matches(s, i)).map(|i| (s, i)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = "section,position,status,text,priority,due\nNext,2,open,Second,,\nToday,1,done,Ship,high,2026-10-20\nNext,1,open,First,,\n";
+        let tasks = parse(&Config::default(), csv, ImportFormat::Csv).unwrap();
+        let lines: Vec<(Option<String>, String)> = tasks.iter().map(|t| (t.section.clone(), t.task.to_markdown())).collect();
+        assert_eq!(
+            lines,
+            [
+                (Some("Next".to_string()), "- [ ] First".to_string()),
+                (Some("Next".to_string()), "- [ ] Second".to_string()),
+                (Some("Today".to_string()), "- [x] Ship ⏫ 📅 2026-10-20".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_merge() {
+        let mut todo = TodoFile::parse("## Today\n- [ ] Pay rent 📅 2026-11-01\n- [ ] Ship 🆔 rel001\n\n## Next\n- [ ] Keep me\n").unwrap();
+        let tasks = vec![
+            ImportedTask::new(Some("Today".to_string()), Task::from_line("- [x] Pay rent 📅 2026-11-03 ✅ 2026-10-18").unwrap()),
+            ImportedTask::new(Some("Next".to_string()), Task::from_line("- [ ] Ship it 🆔 rel001").unwrap()),
+            ImportedTask::new(None, Task::from_line("- [ ] Brand new").unwrap()),
+        ];
+        assert_eq!(apply(&mut todo, tasks, "Inbox", false), (1, 2));
+
+        let today = &todo.sections[todo.find_section("Today").unwrap()];
+        assert_eq!(today.tasks.len(), 1);
+        assert_eq!(today.tasks[0].to_markdown(), "- [x] Pay rent 📅 2026-11-03 ✅ 2026-10-18");
+        let next = &todo.sections[todo.find_section("Next").unwrap()];
+        let texts: Vec<&str> = next.tasks.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(texts, ["Keep me", "Ship it 🆔 rel001"]);
+        assert_eq!(todo.sections[todo.find_section("Inbox").unwrap()].tasks[0].text, "Brand new");
+    }
+
+    #[test]
+    fn test_apply_source() {
+        let mut todo = TodoFile::parse(
+            "## Today\n- [ ] Crash on empty file [source:: github:acme/mdtodo#12] [carried:: 2] ⏫ ⏳ 2026-10-20 🆔 crash1 ⛔ spec01\n",
+        )
+        .unwrap();
+        let tasks = vec![ImportedTask::new(
+            None,
+            Task::from_line("- [x] Crash on empty files [source:: github:acme/mdtodo#12] ➕ 2026-10-01 ✅ 2026-10-18").unwrap(),
+        )];
+        assert_eq!(apply(&mut todo, tasks, "Inbox", false), (0, 1));
+
+        assert_eq!(todo.find_section("Inbox"), None);
+        let task = &todo.sections[0].tasks[0];
+        assert!(task.done);
+        assert_eq!(task.summary(), "Crash on empty files [source:: github:acme/mdtodo#12] [carried:: 2]");
+        assert_eq!(task.priority(), Some(1));
+        assert_eq!(task.field(CREATED).as_deref(), Some("2026-10-01"));
+        assert_eq!(task.field(SCHEDULED).as_deref(), Some("2026-10-20"));
+        assert_eq!(task.field(ID).as_deref(), Some("crash1"));
+        assert_eq!(task.field(DEPENDS_ON).as_deref(), Some("spec01"));
+    }
+
+    #[test]
+    fn test_apply_replace() {
+        let mut todo = TodoFile::parse("## Today\nNotes stay here.\n- [ ] Old task\n\n## Next\n- [ ] Also old\n").unwrap();
+        let tasks = vec![ImportedTask::new(Some("Next".to_string()), Task::from_line("- [ ] Fresh").unwrap())];
+        assert_eq!(apply(&mut todo, tasks, "Inbox", true), (1, 0));
+
+        let content = todo.to_string();
+        assert!(content.contains("## Today\nNotes stay here.\n"));
+        assert!(!content.contains("old"));
+        assert!(!content.contains("Old task"));
+        assert_eq!(todo.sections[todo.find_section("Next").unwrap()].tasks[0].text, "Fresh");
+    }
+}
@@ -1,3 +1,24 @@
 pub mod config;
 pub mod markdown;
+pub mod picker;
+pub mod records;
+pub mod state;
+pub mod stats;
+pub mod todotxt;
 pub mod commands;
+pub mod daily;
+pub mod dates;
+pub mod deps;
+pub mod done_log;
+pub mod export;
+pub mod github;
+pub mod graph;
+pub mod html;
+pub mod ics;
+pub mod import;
+pub mod lock;
+pub mod taskwarrior;
+pub mod server;
+pub mod vault;
+pub mod tui;
+pub mod watch;
@@ -0,0 +1,62 @@
+use crate::config::Config;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// An exclusive advisory lock on a TODO file, released when dropped.
+/// It is held on a hidden sidecar file (`.TODO.md.lock`) rather than the
+/// file itself, so editors that replace the file on save do not break it.
+pub struct FileLock {
+    _file: File,
+}
+
+pub fn lock_path(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    path.with_file_name(format!(".{}.lock", name))
+}
+
+/// Blocks until no other mdtodo process (CLI or `serve`) holds the lock.
+pub fn exclusive(path: &Path) -> Result<FileLock, Box<dyn std::error::Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path(path))?;
+    file.lock()?;
+    Ok(FileLock { _file: file })
+}
+
+/// Locks the profile's TODO.md. Commands that write vault notes or the
+/// done log take this same lock, so one process never waits on itself.
+pub fn todo(config: &Config) -> Result<FileLock, Box<dyn std::error::Error>> {
+    exclusive(&config.todo_path()?)
+}
+
+/// Modification time of `path`, to notice edits made while unlocked.
+pub fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_path() {
+        assert_eq!(lock_path(Path::new("/notes/TODO.md")), PathBuf::from("/notes/.TODO.md.lock"));
+    }
+
+    #[test]
+    fn test_exclusive() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("TODO.md");
+        let held = exclusive(&path).unwrap();
+        let other = File::open(lock_path(&path)).unwrap();
+        assert!(other.try_lock().is_err());
+        drop(held);
+        assert!(other.try_lock().is_ok());
+    }
+}
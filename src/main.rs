@@ -1,14 +1,26 @@
-mod config;
-mod markdown;
-mod commands;
 
 use clap::{Parser, Subcommand};
+use chrono::Duration;
+use mdtodo::commands;
+use mdtodo::config::{AutoArchive, Config};
+use mdtodo::export::ExportFormat;
+use mdtodo::graph::GraphFormat;
+use mdtodo::html::RenderFormat;
+use mdtodo::import::ImportFormat;
+use mdtodo::picker;
+use mdtodo::server;
+use mdtodo::stats::GroupBy;
+use mdtodo::tui;
 use std::process;
 
 #[derive(Parser)]
 #[command(name = "mdtodo")]
 #[command(about = "A Markdown TODO CLI with section support", long_about = None)]
 struct Cli {
+    /// Named profile from config.toml (defaults to `default_profile`)
+    #[arg(short, long, global = true)]
+    profile: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -19,69 +31,347 @@ enum Commands {
     List {
         /// Section name (optional)
         section: Option<String>,
+        /// Only open tasks that are not blocked or scheduled for later
+        #[arg(long)]
+        ready: bool,
     },
     /// Add a task to a section
     Add {
-        /// Section name
+        /// Section name (with --daily: heading in today's note, optional)
         section: String,
         /// Task text
-        text: String,
+        text: Option<String>,
+        /// Append to today's daily note instead of TODO.md
+        #[arg(long)]
+        daily: bool,
+    },
+    /// Capture tasks from stdin (one per line) or a drop directory into Inbox
+    Capture {
+        /// Section to capture into (defaults to inbox_section)
+        #[arg(long)]
+        to: Option<String>,
+        /// Ingest *.txt / *.md files from a directory (defaults to drop_dir)
+        #[arg(long, num_args = 0..=1, value_name = "DIR")]
+        drop_dir: Option<Option<String>>,
     },
     /// Mark a task as done
     Done {
-        /// Task reference (Section:number)
-        task: String,
+        /// Task reference (Section:number); omit or use `?` to pick
+        task: Option<String>,
+        /// Complete the task even if its blockers are open
+        #[arg(long)]
+        force: bool,
+    },
+    /// Export tasks to another format
+    Export {
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+        /// Write to this file instead of stdout
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Import tasks from another format into TODO.md
+    Import {
+        #[arg(long, value_enum, alias = "format")]
+        from: ImportFormat,
+        /// File to read
+        file: String,
+        /// Section for tasks the file does not assign one (defaults to inbox_section)
+        #[arg(long)]
+        to: Option<String>,
+        /// Update matching tasks and add the rest (default)
+        #[arg(long, overrides_with = "replace")]
+        merge: bool,
+        /// Drop every task in TODO.md and keep only the imported ones
+        #[arg(long, overrides_with = "merge")]
+        replace: bool,
+    },
+    /// Render the board and done log as a static page
+    Render {
+        #[arg(long, value_enum, default_value = "html")]
+        format: RenderFormat,
+        /// Write index.html into this directory instead of stdout
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Serve a JSON REST API for TODO.md on localhost
+    Serve {
+        #[arg(long, default_value_t = 7373)]
+        port: u16,
+        /// Send CORS headers allowing this origin (e.g. http://localhost:3000)
+        #[arg(long)]
+        allow_origin: Option<String>,
+    },
+    /// Print the dependency graph of open tasks
+    Graph {
+        #[arg(long, value_enum, default_value = "dot")]
+        format: GraphFormat,
+    },
+    /// Make a task depend on another (🆔 / ⛔)
+    Depend {
+        /// Task that waits (Section:number; omit or use `?` to pick), then the
+        /// task it waits on
+        #[arg(num_args = 1..=3, value_name = "[TASK] [on] BLOCKER", required = true)]
+        args: Vec<String>,
     },
     /// Mark a task as undone
     Undo {
-        /// Task reference (Section:number)
-        task: String,
+        /// Task reference (Section:number); omit or use `?` to pick
+        task: Option<String>,
     },
     /// Move a task to another section
     Move {
-        /// Task reference (Section:number or Section:number,number,...)
-        task: String,
-        /// Destination section
-        dest: String,
+        /// Task reference (Section:number or Section:number,number,...; omit or
+        /// use `?` to pick), then the destination section
+        #[arg(num_args = 1..=2, value_name = "[TASK] DEST", required = true)]
+        args: Vec<String>,
     },
     /// Archive completed tasks to done_list.md
     Archive {
         /// Task reference (Section:number,number,... or Section:all)
-        task: String,
+        #[arg(required_unless_present_any = ["compact", "all_sections", "older_than"])]
+        task: Option<String>,
+        /// Rewrite the existing done log into the configured done_layout
+        #[arg(long, conflicts_with_all = ["task", "all_sections", "older_than"])]
+        compact: bool,
+        /// Archive completed tasks from every section
+        #[arg(long, conflicts_with = "task")]
+        all_sections: bool,
+        /// Only tasks completed longer ago than this (e.g. 7d, 2w); implies --all-sections
+        #[arg(long, conflicts_with = "task")]
+        older_than: Option<String>,
+    },
+    /// Roll over unfinished tasks and archive completed ones (once per day)
+    Rollover {
+        /// Run again even if a rollover already happened today
+        #[arg(long)]
+        force: bool,
+    },
+    /// Park a task in a holding section until a date (⏳)
+    Snooze {
+        /// Task reference (Section:number; omit or use `?` to pick), then the
+        /// date to wake up: YYYY-MM-DD, today, tomorrow, monday, +3d, +2w
+        #[arg(num_args = 1..=3, value_name = "[TASK] [until] DATE", required = true)]
+        args: Vec<String>,
+        /// Holding section (defaults to snooze_section)
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Move snoozed tasks whose date has arrived back to their section
+    Wake,
+    /// Move a task to the waiting section, noting who it is waiting on
+    Wait {
+        /// Task reference (Section:number); omit or use `?` to pick
+        task: Option<String>,
+        /// Person or team the task is waiting on
+        #[arg(long)]
+        on: String,
+        /// Date to follow up: YYYY-MM-DD, tomorrow, friday, +3d, ...
+        #[arg(long)]
+        follow_up: Option<String>,
+    },
+    /// List waiting tasks grouped by person
+    Waiting,
+    /// Walk through stale tasks section by section
+    Review,
+    /// Show task counts and whether a review is overdue
+    Status,
+    /// Restore archived tasks from done_list.md to TODO.md
+    Unarchive {
+        /// Done log reference (date/Section:number or date/Section:number,number,...)
+        entry: String,
+        /// Mark the restored tasks as not done
+        #[arg(long)]
+        reopen: bool,
     },
     /// Delete tasks
     Delete {
-        /// Task reference (Section:number or Section:number,number,...)
-        task: String,
+        /// Task reference (Section:number or Section:number,number,...); omit or use `?` to pick
+        task: Option<String>,
     },
     /// Edit a task's text
     Edit {
-        /// Task reference (Section:number)
-        task: String,
-        /// New text
-        text: String,
+        /// Task reference (Section:number; omit or use `?` to pick), then the
+        /// new text
+        #[arg(num_args = 1..=2, value_name = "[TASK] TEXT", required = true)]
+        args: Vec<String>,
+    },
+    /// Report completions, open tasks, streaks and a heatmap
+    Stats {
+        /// Start date: YYYY-MM-DD or an offset like -30d
+        #[arg(long, allow_hyphen_values = true)]
+        since: Option<String>,
+        /// End date (defaults to today)
+        #[arg(long, allow_hyphen_values = true)]
+        until: Option<String>,
+        /// How to group completed counts
+        #[arg(long, value_enum, default_value = "week")]
+        by: GroupBy,
     },
+    /// Search the archived done log
+    DoneLog {
+        /// Only this archive date (YYYY-MM-DD, today, yesterday, -3d, ...)
+        #[arg(long, allow_hyphen_values = true)]
+        date: Option<String>,
+        /// Archive dates on or after this date
+        #[arg(long, allow_hyphen_values = true)]
+        since: Option<String>,
+        /// Archive dates on or before this date
+        #[arg(long, allow_hyphen_values = true)]
+        until: Option<String>,
+        /// Only this section
+        #[arg(long)]
+        section: Option<String>,
+        /// Case-insensitive regular expression matched against task text
+        #[arg(long)]
+        grep: Option<String>,
+        /// Print matches as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print every task as `reference<TAB>text` for an external picker
+    Pick,
+    /// Print a JSON line for every change made to TODO.md
+    Watch,
     /// Initialize TODO.md with default template
     Init,
+    /// Open the interactive terminal UI
+    Tui,
+    /// Show today's daily note, creating it from the template if needed
+    Today,
+    /// Carry unfinished tasks from the previous daily note into today's
+    Carry,
 }
 
 fn main() {
     let cli = Cli::parse();
 
-    let result = match cli.command {
-        Commands::List { section } => commands::list(section),
-        Commands::Add { section, text } => commands::add(section, text),
-        Commands::Done { task } => commands::done(task),
-        Commands::Undo { task } => commands::undo(task),
-        Commands::Move { task, dest } => commands::move_task(task, dest),
-        Commands::Archive { task } => commands::archive(task),
-        Commands::Delete { task } => commands::delete(task),
-        Commands::Edit { task, text } => commands::edit(task, text),
-        Commands::Init => commands::init(),
-    };
+    let result = Config::load(cli.profile.as_deref()).and_then(|config| run(&config, cli.command));
 
     if let Err(e) = result {
         eprintln!("Error: {}", e);
         process::exit(1);
     }
 }
+
+fn run(config: &Config, command: Commands) -> Result<(), Box<dyn std::error::Error>> {
+    // Each of these takes the TODO.md lock itself. `watch` only reads, so it
+    // never writes behind a running `serve`.
+    let maintain = !matches!(command, Commands::Init | Commands::Watch);
+    if maintain && config.auto_wake && !matches!(command, Commands::Wake) {
+        commands::wake_due(config)?;
+    }
+    if maintain && config.auto_archive == AutoArchive::AfterDays {
+        let cutoff = config.today()? - Duration::days(config.auto_archive_days);
+        commands::archive_completed(config, Some(cutoff))?;
+    }
+
+    match command {
+        Commands::List { section, ready } => commands::list(config, section, ready),
+        Commands::Add { section, text, daily } => match (daily, text) {
+            (true, Some(text)) => commands::add_daily(config, Some(section), text),
+            (true, None) => commands::add_daily(config, None, section),
+            (false, Some(text)) => commands::add(config, section, text),
+            (false, None) => Err("Missing task text".into()),
+        },
+        Commands::Capture { to, drop_dir } => match drop_dir {
+            Some(dir) => commands::capture_drop_dir(config, dir, to),
+            None => commands::capture(config, to),
+        },
+        Commands::Done { task, force } => commands::done(config, picker::resolve_arg(config, task)?, force),
+        Commands::Graph { format } => commands::graph(config, format),
+        Commands::Export { format, out } => commands::export(config, format, out),
+        // The last of --merge / --replace wins.
+        Commands::Import { from, file, to, merge, replace } => {
+            commands::import(config, from, file, to, replace && !merge)
+        }
+        Commands::Serve { port, allow_origin } => server::serve(config, port, allow_origin),
+        Commands::Render { format, out } => commands::render(config, format, out),
+        Commands::Depend { args } => {
+            let (task, blocker) = split_task_args(args, Some("on"))
+                .ok_or("Usage: depend [<Section:number>] on <Section:number>")?;
+            commands::depend(
+                config,
+                picker::resolve_arg(config, task)?,
+                picker::resolve_arg(config, Some(blocker))?,
+            )
+        }
+        Commands::Undo { task } => commands::undo(config, picker::resolve_arg(config, task)?),
+        Commands::Move { args } => {
+            let (task, dest) = split_task_args(args, None).ok_or("Usage: move [<Section:number>] <section>")?;
+            commands::move_task(config, picker::resolve_arg(config, task)?, dest)
+        }
+        Commands::Archive {
+            task,
+            compact,
+            all_sections,
+            older_than,
+        } => {
+            if compact {
+                commands::compact_archive(config)
+            } else if all_sections || older_than.is_some() {
+                commands::archive_all(config, older_than)
+            } else {
+                commands::archive(config, picker::resolve_arg(config, task)?)
+            }
+        }
+        Commands::Rollover { force } => commands::rollover(config, force),
+        Commands::Snooze { args, to } => {
+            let (task, date) = split_task_args(args, Some("until"))
+                .ok_or("Usage: snooze [<Section:number>] until <date>")?;
+            commands::snooze(config, picker::resolve_arg(config, task)?, date, to)
+        }
+        Commands::Wake => commands::wake(config),
+        Commands::Wait { task, on, follow_up } => {
+            commands::wait(config, picker::resolve_arg(config, task)?, on, follow_up)
+        }
+        Commands::Waiting => commands::waiting(config),
+        Commands::Review => commands::review(config),
+        Commands::Status => commands::status(config),
+        Commands::Unarchive { entry, reopen } => commands::unarchive(config, entry, reopen),
+        Commands::Delete { task } => commands::delete(config, picker::resolve_arg(config, task)?),
+        Commands::Edit { args } => {
+            let (task, text) = split_task_args(args, None).ok_or("Usage: edit [<Section:number>] <text>")?;
+            commands::edit(config, picker::resolve_arg(config, task)?, text)
+        }
+        Commands::Stats { since, until, by } => commands::stats(config, since, until, by),
+        Commands::DoneLog {
+            date,
+            since,
+            until,
+            section,
+            grep,
+            json,
+        } => commands::done_log(
+            config,
+            commands::DoneLogQuery {
+                date,
+                since,
+                until,
+                section,
+                grep,
+                json,
+            },
+        ),
+        Commands::Pick => commands::pick(config),
+        Commands::Watch => commands::watch(config),
+        Commands::Init => commands::init(config),
+        Commands::Tui => tui::run(config),
+        Commands::Today => commands::today(config),
+        Commands::Carry => commands::carry(config),
+    }
+}
+
+/// Splits `[TASK] [keyword] VALUE` arguments into the optional task
+/// reference (left for the picker when missing) and the final value.
+fn split_task_args(mut args: Vec<String>, keyword: Option<&str>) -> Option<(Option<String>, String)> {
+    let value = args.pop()?;
+    if keyword.is_some() && args.last().map(String::as_str) == keyword {
+        args.pop();
+    }
+    match args.len() {
+        0 => Some((None, value)),
+        1 => Some((args.pop(), value)),
+        _ => None,
+    }
+}
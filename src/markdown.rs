@@ -1,8 +1,25 @@
+use chrono::NaiveDate;
 use regex::Regex;
 use std::collections::HashMap;
+use std::fmt;
 use std::fs;
 use std::path::Path;
 
+/// Obsidian Tasks scheduled date (`⏳ YYYY-MM-DD`).
+pub const SCHEDULED: &str = "⏳";
+/// Obsidian Tasks created date (`➕ YYYY-MM-DD`).
+pub const CREATED: &str = "➕";
+/// Obsidian Tasks due date (`📅 YYYY-MM-DD`).
+pub const DUE: &str = "📅";
+/// Obsidian Tasks start date (`🛫 YYYY-MM-DD`).
+pub const START: &str = "🛫";
+/// Obsidian Tasks priority markers, highest first.
+pub const PRIORITIES: [&str; 5] = ["🔺", "⏫", "🔼", "🔽", "⏬"];
+/// Obsidian Tasks task id (`🆔 abc123`).
+pub const ID: &str = "🆔";
+/// Obsidian Tasks dependencies (`⛔ abc123,def456`).
+pub const DEPENDS_ON: &str = "⛔";
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Task {
     pub text: String,
@@ -43,13 +60,120 @@ impl Task {
             None
         }
     }
+
+    /// Value of an Obsidian Tasks emoji field such as `⏳ 2026-10-20`.
+    pub fn field(&self, emoji: &str) -> Option<String> {
+        emoji_field_regex(emoji)
+            .captures(&self.text)
+            .map(|caps| caps[1].to_string())
+    }
+
+    pub fn date_field(&self, emoji: &str) -> Option<NaiveDate> {
+        self.field(emoji)
+            .and_then(|value| NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok())
+    }
+
+    /// Sets an emoji field, replacing an existing value or appending one.
+    pub fn set_field(&mut self, emoji: &str, value: &str) {
+        let regex = emoji_field_regex(emoji);
+        let field = format!("{} {}", emoji, value);
+        if regex.is_match(&self.text) {
+            self.text = regex.replace(&self.text, regex::NoExpand(&field)).to_string();
+        } else {
+            self.text = format!("{} {}", self.text, field);
+        }
+    }
+
+    pub fn remove_field(&mut self, emoji: &str) {
+        let regex = Regex::new(&format!(r"\s*{}\x{{FE0F}}?\s*\S+", regex::escape(emoji))).unwrap();
+        self.text = regex.replace_all(&self.text, "").to_string();
+    }
+
+    /// Text without the Obsidian Tasks date, priority, id and dependency
+    /// fields.
+    pub fn summary(&self) -> String {
+        let mut task = self.clone();
+        for emoji in [DUE, START, SCHEDULED, CREATED, ID, DEPENDS_ON] {
+            task.remove_field(emoji);
+        }
+        task.set_priority(None);
+        task.text.trim().to_string()
+    }
+
+    /// Index into `PRIORITIES` of the task's priority marker, 0 being the
+    /// highest.
+    pub fn priority(&self) -> Option<usize> {
+        PRIORITIES.iter().position(|marker| self.text.contains(marker))
+    }
+
+    /// Replaces the priority marker, or removes it with `None`.
+    pub fn set_priority(&mut self, priority: Option<usize>) {
+        for marker in PRIORITIES {
+            let regex = Regex::new(&format!(r"\s*{}\x{{FE0F}}?", regex::escape(marker))).unwrap();
+            self.text = regex.replace_all(&self.text, "").to_string();
+        }
+        if let Some(marker) = priority.and_then(|p| PRIORITIES.get(p)) {
+            self.text = format!("{} {}", self.text, marker);
+        }
+    }
+
+    /// `#tags` in the text, without the `#`.
+    pub fn tags(&self) -> Vec<String> {
+        let regex = Regex::new(r"(?:^|\s)#([^\s#\[\]]+)").unwrap();
+        regex
+            .captures_iter(&self.text)
+            .map(|caps| caps[1].to_string())
+            .filter(|tag| !tag.chars().all(|c| c.is_ascii_digit()))
+            .collect()
+    }
+
+    /// Value of a Dataview-style inline field `[key:: value]` in the text.
+    pub fn inline_field(&self, key: &str) -> Option<String> {
+        inline_field_regex(key)
+            .captures(&self.text)
+            .map(|caps| caps[1].trim().to_string())
+    }
+
+    /// Sets `[key:: value]`, replacing an existing field or appending one.
+    pub fn set_inline_field(&mut self, key: &str, value: &str) {
+        let regex = inline_field_regex(key);
+        let field = format!("[{}:: {}]", key, value);
+        if regex.is_match(&self.text) {
+            self.text = regex.replace(&self.text, regex::NoExpand(&field)).to_string();
+        } else {
+            self.text = format!("{} {}", self.text, field);
+        }
+    }
+
+    pub fn remove_inline_field(&mut self, key: &str) {
+        let regex = Regex::new(&format!(r"\s*\[{}::[^\]]*\]", regex::escape(key))).unwrap();
+        self.text = regex.replace_all(&self.text, "").to_string();
+    }
+}
+
+fn emoji_field_regex(emoji: &str) -> Regex {
+    Regex::new(&format!(r"{}\x{{FE0F}}?\s*(\S+)", regex::escape(emoji))).unwrap()
+}
+
+fn inline_field_regex(key: &str) -> Regex {
+    Regex::new(&format!(r"\[{}::([^\]]*)\]", regex::escape(key))).unwrap()
 }
 
 #[derive(Debug)]
 pub struct Section {
     pub name: String,
     pub tasks: Vec<Task>,
-    pub other_lines: Vec<(usize, String)>,
+    /// The heading and body as read from the file, so saving rewrites only
+    /// the task lines that changed. `None` for sections created in memory.
+    source: Option<(String, Vec<SourceLine>)>,
+}
+
+/// A body line as read: prose and blank lines verbatim, task lines with
+/// their indentation and the task they held, to tell which ones changed.
+#[derive(Debug)]
+enum SourceLine {
+    Text(String),
+    Task { raw: String, indent: String, task: Task },
 }
 
 impl Section {
@@ -57,9 +181,148 @@ impl Section {
         Section {
             name,
             tasks: Vec::new(),
-            other_lines: Vec::new(),
+            source: None,
+        }
+    }
+
+    fn write(&self, out: &mut String) {
+        let lines = match &self.source {
+            Some((heading, lines)) => {
+                out.push_str(heading);
+                out.push('\n');
+                lines
+            }
+            None => {
+                if !out.is_empty() && !out.ends_with("\n\n") {
+                    out.push('\n');
+                }
+                out.push_str(&format!("## {}\n", self.name));
+                for task in &self.tasks {
+                    out.push_str(&task.to_markdown());
+                    out.push('\n');
+                }
+                out.push('\n');
+                return;
+            }
+        };
+
+        let slots: Vec<&Task> = lines
+            .iter()
+            .filter_map(|line| match line {
+                SourceLine::Task { task, .. } => Some(task),
+                SourceLine::Text(_) => None,
+            })
+            .collect();
+        let (assigned, inserted) = align(&slots, &self.tasks);
+        let push_inserted = |out: &mut String, after: Option<usize>| {
+            for (_, idx) in inserted.iter().filter(|(slot, _)| *slot == after) {
+                out.push_str(&self.tasks[*idx].to_markdown());
+                out.push('\n');
+            }
+        };
+
+        // New tasks without a neighbour go where the first task was, or
+        // after the last non-blank line of a section without tasks.
+        let first_anchor = if slots.is_empty() {
+            lines
+                .iter()
+                .rposition(|line| matches!(line, SourceLine::Text(t) if !t.trim().is_empty()))
+                .map_or(0, |i| i + 1)
+        } else {
+            lines.iter().position(|line| matches!(line, SourceLine::Task { .. })).unwrap_or(0)
+        };
+
+        let mut slot = 0;
+        for (i, line) in lines.iter().enumerate() {
+            if i == first_anchor {
+                push_inserted(out, None);
+            }
+            match line {
+                SourceLine::Text(text) => out.push_str(text),
+                SourceLine::Task { raw, indent, task } => {
+                    if let Some(idx) = assigned[slot] {
+                        let current = &self.tasks[idx];
+                        if current == task {
+                            out.push_str(raw);
+                        } else {
+                            out.push_str(indent);
+                            out.push_str(&current.to_markdown());
+                        }
+                        out.push('\n');
+                    }
+                    push_inserted(out, Some(slot));
+                    slot += 1;
+                    continue;
+                }
+            }
+            out.push('\n');
+        }
+        if first_anchor >= lines.len() {
+            push_inserted(out, None);
+        }
+    }
+}
+
+/// A task index to write after the given slot (`None`: before the first).
+type Insertion = (Option<usize>, usize);
+
+/// Matches the tasks a section had when it was read (`slots`) with the
+/// ones it has now. Unchanged tasks are paired by longest common
+/// subsequence, and the changed ones between them by position, which
+/// covers edits in place. Returns the task each slot now holds (`None`
+/// when it was removed) and the remaining tasks with the slot they follow
+/// (`None` for before the first slot).
+fn align(slots: &[&Task], tasks: &[Task]) -> (Vec<Option<usize>>, Vec<Insertion>) {
+    let (n, m) = (slots.len(), tasks.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if *slots[i] == tasks[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut anchors = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if *slots[i] == tasks[j] {
+            anchors.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    anchors.push((n, m));
+
+    let mut assigned = vec![None; n];
+    let mut inserted = Vec::new();
+    let (mut slot, mut task) = (0, 0);
+    let mut last: Option<usize> = None;
+    for (anchor_slot, anchor_task) in anchors {
+        while slot < anchor_slot && task < anchor_task {
+            assigned[slot] = Some(task);
+            last = Some(slot);
+            slot += 1;
+            task += 1;
+        }
+        while task < anchor_task {
+            inserted.push((last, task));
+            task += 1;
+        }
+        slot = anchor_slot;
+        if anchor_slot < n {
+            assigned[anchor_slot] = Some(anchor_task);
+            last = Some(anchor_slot);
+            slot += 1;
+            task = anchor_task + 1;
         }
     }
+    (assigned, inserted)
 }
 
 #[derive(Debug)]
@@ -85,32 +348,41 @@ impl TodoFile {
         let lines: Vec<&str> = content.lines().collect();
         let mut sections = Vec::new();
         let mut header_lines = Vec::new();
-        let mut current_section: Option<Section> = None;
-        let mut line_idx_in_section = 0;
+        // The section being read, with its heading line and body so far.
+        let mut current_section: Option<(Section, String, Vec<SourceLine>)> = None;
+        let finish = |(mut section, heading, body): (Section, String, Vec<SourceLine>)| {
+            section.source = Some((heading, body));
+            section
+        };
 
         let section_regex = Regex::new(r"^##\s+(.+)$").unwrap();
 
         for line in lines {
             if let Some(caps) = section_regex.captures(line) {
-                if let Some(section) = current_section.take() {
-                    sections.push(section);
+                if let Some(current) = current_section.take() {
+                    sections.push(finish(current));
                 }
-                current_section = Some(Section::new(caps[1].to_string()));
-                line_idx_in_section = 0;
-            } else if let Some(ref mut section) = current_section {
-                if let Some(task) = Task::from_line(line) {
-                    section.tasks.push(task);
-                } else if !line.trim().is_empty() {
-                    section.other_lines.push((line_idx_in_section, line.to_string()));
+                current_section = Some((Section::new(caps[1].to_string()), line.to_string(), Vec::new()));
+            } else if let Some((section, _, body)) = &mut current_section {
+                match Task::from_line(line) {
+                    Some(task) => {
+                        let indent = &line[..line.len() - line.trim_start().len()];
+                        body.push(SourceLine::Task {
+                            raw: line.to_string(),
+                            indent: indent.to_string(),
+                            task: task.clone(),
+                        });
+                        section.tasks.push(task);
+                    }
+                    None => body.push(SourceLine::Text(line.to_string())),
                 }
-                line_idx_in_section += 1;
             } else {
                 header_lines.push(line.to_string());
             }
         }
 
-        if let Some(section) = current_section {
-            sections.push(section);
+        if let Some(current) = current_section {
+            sections.push(finish(current));
         }
 
         Ok(TodoFile {
@@ -128,40 +400,6 @@ impl TodoFile {
         Ok(())
     }
 
-    pub fn to_string(&self) -> String {
-        let mut result = String::new();
-
-        for line in &self.header_lines {
-            result.push_str(line);
-            result.push('\n');
-        }
-
-        for section in &self.sections {
-            result.push_str(&format!("## {}\n", section.name));
-
-            let mut all_lines: Vec<(usize, String)> = Vec::new();
-
-            for (idx, task) in section.tasks.iter().enumerate() {
-                all_lines.push((idx, task.to_markdown()));
-            }
-
-            for (idx, line) in &section.other_lines {
-                all_lines.push((*idx, line.clone()));
-            }
-
-            all_lines.sort_by_key(|(idx, _)| *idx);
-
-            for (_, line) in all_lines {
-                result.push_str(&line);
-                result.push('\n');
-            }
-
-            result.push('\n');
-        }
-
-        result
-    }
-
     pub fn find_section(&self, name: &str) -> Option<usize> {
         self.sections.iter().position(|s| s.name.eq_ignore_ascii_case(name))
     }
@@ -192,6 +430,23 @@ impl TodoFile {
     }
 }
 
+impl fmt::Display for TodoFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut result = String::new();
+
+        for line in &self.header_lines {
+            result.push_str(line);
+            result.push('\n');
+        }
+
+        for section in &self.sections {
+            section.write(&mut result);
+        }
+
+        f.write_str(&result)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -209,6 +464,51 @@ mod tests {
         assert_eq!(done_task.done_date, Some("2026-02-13".to_string()));
     }
 
+    #[test]
+    fn test_emoji_fields() {
+        let mut task = Task::from_line("- [ ] Call dentist ⏳ 2026-10-20").unwrap();
+        assert_eq!(task.field(SCHEDULED), Some("2026-10-20".to_string()));
+        assert_eq!(task.date_field(SCHEDULED), NaiveDate::from_ymd_opt(2026, 10, 20));
+
+        task.set_field(SCHEDULED, "2026-11-01");
+        assert_eq!(task.text, "Call dentist ⏳ 2026-11-01");
+
+        task.remove_field(SCHEDULED);
+        assert_eq!(task.text, "Call dentist");
+        assert_eq!(task.field(SCHEDULED), None);
+
+        task.set_field(SCHEDULED, "2026-12-24");
+        assert_eq!(task.to_markdown(), "- [ ] Call dentist ⏳ 2026-12-24");
+    }
+
+    #[test]
+    fn test_inline_fields() {
+        let mut task = Task::from_line("- [ ] Write report").unwrap();
+        assert_eq!(task.inline_field("carried"), None);
+
+        task.set_inline_field("carried", "1");
+        assert_eq!(task.text, "Write report [carried:: 1]");
+
+        task.set_inline_field("carried", "2");
+        assert_eq!(task.inline_field("carried"), Some("2".to_string()));
+        assert_eq!(task.text, "Write report [carried:: 2]");
+
+        task.remove_inline_field("carried");
+        assert_eq!(task.text, "Write report");
+    }
+
+    #[test]
+    fn test_priority_and_tags() {
+        let mut task = Task::from_line("- [ ] Call #work/client about #42 ⏫ 📅 2026-10-20").unwrap();
+        assert_eq!(task.priority(), Some(1));
+        assert_eq!(task.tags(), vec!["work/client"]);
+
+        task.set_priority(Some(4));
+        assert_eq!(task.text, "Call #work/client about #42 📅 2026-10-20 ⏬");
+        task.set_priority(None);
+        assert_eq!(task.priority(), None);
+    }
+
     #[test]
     fn test_todo_file_parsing() {
         let content = r#"# TODO
@@ -228,4 +528,25 @@ mod tests {
         assert_eq!(todo.sections[1].name, "Next");
         assert_eq!(todo.sections[1].tasks.len(), 1);
     }
+
+    #[test]
+    fn test_round_trip_keeps_note_layout() {
+        let content = "# Alpha\n\nProject notes.\n\n## Tasks\n\nIntro paragraph.\n\n- [ ] Task A\n  - [ ] Subtask A1\n    Details under the subtask.\n- [ ] Task B\n\nClosing thoughts.\n\n## Log\nNothing yet.\n";
+        let mut todo = TodoFile::parse(content).unwrap();
+        assert_eq!(todo.to_string(), content);
+
+        let tasks = &mut todo.sections[0].tasks;
+        tasks[1].done = true;
+        tasks[1].done_date = Some("2026-10-18".to_string());
+        tasks.remove(2);
+        tasks.push(Task::from_line("- [ ] Task C").unwrap());
+        todo.sections[1].tasks.push(Task::from_line("- [ ] First log task").unwrap());
+        let new_section = todo.get_or_create_section("Later");
+        todo.sections[new_section].tasks.push(Task::from_line("- [ ] Someday").unwrap());
+
+        assert_eq!(
+            todo.to_string(),
+            "# Alpha\n\nProject notes.\n\n## Tasks\n\nIntro paragraph.\n\n- [ ] Task A\n  - [x] Subtask A1 ✅ 2026-10-18\n    Details under the subtask.\n- [ ] Task C\n\nClosing thoughts.\n\n## Log\nNothing yet.\n- [ ] First log task\n\n## Later\n- [ ] Someday\n\n"
+        );
+    }
 }
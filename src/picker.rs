@@ -0,0 +1,282 @@
+use crate::config::Config;
+use crate::markdown::TodoFile;
+use crate::vault;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{List, ListItem, ListState, Paragraph};
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    /// Reference accepted by the task commands, e.g. `Today:3`.
+    pub reference: String,
+    /// Text matched against the query: reference, checkbox and task text.
+    pub label: String,
+}
+
+/// Every task in TODO.md, or in every vault file when vault mode is on.
+pub fn candidates(config: &Config) -> Result<Vec<Candidate>, Box<dyn std::error::Error>> {
+    let mut result = Vec::new();
+    for (prefix, todo) in vault::todo_files(config)? {
+        push_candidates(&mut result, &todo, &prefix);
+    }
+    Ok(result)
+}
+
+fn push_candidates(result: &mut Vec<Candidate>, todo: &TodoFile, prefix: &str) {
+    for section in &todo.sections {
+        for (idx, task) in section.tasks.iter().enumerate() {
+            let reference = format!("{}{}:{}", prefix, section.name, idx + 1);
+            let checkbox = if task.done { "[x]" } else { "[ ]" };
+            result.push(Candidate {
+                label: format!("{}\t{} {}", reference, checkbox, task.text),
+                reference,
+            });
+        }
+    }
+}
+
+/// Turns a task argument into a reference. A missing argument or `?` opens
+/// the picker; a line copied from `mdtodo pick` is cut at the first tab.
+pub fn resolve_arg(config: &Config, arg: Option<String>) -> Result<String, Box<dyn std::error::Error>> {
+    match arg.as_deref().map(str::trim) {
+        None | Some("?") => pick(config),
+        Some(reference) => Ok(reference
+            .split('\t')
+            .next()
+            .unwrap_or(reference)
+            .trim()
+            .to_string()),
+    }
+}
+
+pub fn pick(config: &Config) -> Result<String, Box<dyn std::error::Error>> {
+    let candidates = candidates(config)?;
+    if candidates.is_empty() {
+        return Err("No tasks to pick from".into());
+    }
+
+    let selection = match &config.picker_command {
+        Some(command) => run_external(command, &candidates)?,
+        None => {
+            if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+                return Err(
+                    "The picker needs a terminal. Pipe `mdtodo pick` into a picker instead".into(),
+                );
+            }
+            run_builtin(&candidates)?
+        }
+    };
+
+    let selection = selection.ok_or("No task selected")?;
+    resolve_arg(config, Some(selection))
+}
+
+fn run_external(command: &str, candidates: &[Candidate]) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run picker_command '{}': {}", command, e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        for candidate in candidates {
+            writeln!(stdin, "{}", candidate.label)?;
+        }
+    }
+
+    let output = child.wait_with_output()?;
+    let selection = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(str::to_string)
+        .filter(|line| !line.trim().is_empty());
+
+    Ok(selection)
+}
+
+/// Case-insensitive subsequence match. Higher is better; consecutive
+/// characters and matches at word starts score extra. Every occurrence of the
+/// first query character is tried as a starting point.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let query: Vec<char> = query
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+
+    let first = match query.first() {
+        Some(first) => *first,
+        None => return Some(0),
+    };
+
+    (0..text.len())
+        .filter(|&start| text[start] == first)
+        .filter_map(|start| score_from(&query, &text, start))
+        .max()
+}
+
+fn score_from(query: &[char], text: &[char], start: usize) -> Option<i64> {
+    let mut score = 0;
+    let mut pos = start;
+    let mut prev_match: Option<usize> = None;
+
+    for &qc in query {
+        let found = (pos..text.len()).find(|&i| text[i] == qc)?;
+
+        score += 1;
+        if prev_match.is_some_and(|prev| prev + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+
+        prev_match = Some(found);
+        pos = found + 1;
+    }
+
+    Some(score)
+}
+
+/// Candidates matching `query`, best first. Ties keep file order.
+pub fn filter<'a>(candidates: &'a [Candidate], query: &str) -> Vec<&'a Candidate> {
+    let mut scored: Vec<(i64, &Candidate)> = candidates
+        .iter()
+        .filter_map(|c| fuzzy_score(query, &c.label).map(|score| (score, c)))
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, c)| c).collect()
+}
+
+fn run_builtin(candidates: &[Candidate]) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let mut terminal = ratatui::init();
+    let mut query = String::new();
+    let mut selected = 0;
+
+    let result = loop {
+        let matches = filter(candidates, &query);
+        selected = selected.min(matches.len().saturating_sub(1));
+
+        let drawn = terminal.draw(|frame| {
+            let [input, list] =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(frame.area());
+            frame.render_widget(
+                Paragraph::new(format!("> {}▏  ({}/{})", query, matches.len(), candidates.len())),
+                input,
+            );
+            let items: Vec<ListItem> = matches
+                .iter()
+                .map(|c| ListItem::new(Line::from(c.label.replace('\t', "  "))))
+                .collect();
+            let mut state = ListState::default().with_selected(Some(selected));
+            frame.render_stateful_widget(
+                List::new(items).highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+                list,
+                &mut state,
+            );
+        });
+        if let Err(e) = drawn {
+            break Err(e.into());
+        }
+
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e.into()),
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Enter => break Ok(matches.get(selected).map(|c| c.reference.clone())),
+            KeyCode::Esc => break Ok(None),
+            KeyCode::Char('c') if ctrl => break Ok(None),
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Char('p') if ctrl => selected = selected.saturating_sub(1),
+            KeyCode::Down => selected += 1,
+            KeyCode::Char('n') if ctrl => selected += 1,
+            KeyCode::Backspace => {
+                query.pop();
+                selected = 0;
+            }
+            KeyCode::Char(c) => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+    };
+
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(reference: &str, text: &str) -> Candidate {
+        Candidate {
+            reference: reference.to_string(),
+            label: format!("{}\t[ ] {}", reference, text),
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("ctr", "Review contract").is_some());
+        assert!(fuzzy_score("xyz", "Review contract").is_none());
+        assert!(fuzzy_score("", "anything").is_some());
+        assert!(
+            fuzzy_score("con", "Review contract").unwrap()
+                > fuzzy_score("con", "call on Nina").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_filter_orders_by_score() {
+        let candidates = vec![
+            candidate("Next:1", "Call on Nina"),
+            candidate("Backlogs:17", "Review contract"),
+            candidate("Today:1", "Buy milk"),
+        ];
+
+        let matches = filter(&candidates, "contract");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].reference, "Backlogs:17");
+
+        let matches = filter(&candidates, "con");
+        assert_eq!(matches[0].reference, "Backlogs:17");
+        assert_eq!(matches.len(), 2);
+    }
+
+    #[test]
+    fn test_resolve_arg_accepts_picker_lines() {
+        let config = Config::default();
+        assert_eq!(
+            resolve_arg(&config, Some("Backlogs:17\t[ ] Review contract".to_string())).unwrap(),
+            "Backlogs:17"
+        );
+        assert_eq!(resolve_arg(&config, Some("Today:2".to_string())).unwrap(), "Today:2");
+    }
+
+    #[test]
+    fn test_external_picker() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let todo_path = temp.path().join("TODO.md");
+        std::fs::write(&todo_path, "## Today\n- [ ] Buy milk\n- [ ] Review contract\n").unwrap();
+        let config = Config {
+            todo_path: todo_path.to_string_lossy().to_string(),
+            picker_command: Some("grep contract".to_string()),
+            ..Config::default()
+        };
+
+        assert_eq!(resolve_arg(&config, Some("?".to_string())).unwrap(), "Today:2");
+    }
+}
@@ -0,0 +1,226 @@
+use crate::markdown::{Task, TodoFile, CREATED, DEPENDS_ON, DUE, ID, SCHEDULED, START};
+use serde::{Deserialize, Serialize};
+
+/// Priority names matching `PRIORITIES`, highest first.
+const PRIORITY_NAMES: [&str; 5] = ["highest", "high", "medium", "low", "lowest"];
+
+/// One task as a flat row, with its metadata fields split into columns so
+/// they can be edited in a spreadsheet. `text` holds the task text without
+/// those fields; tags stay in the text and `tags` is only informational.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Record {
+    pub section: String,
+    /// 1-based position within the section.
+    #[serde(default)]
+    pub position: Option<usize>,
+    /// `open` or `done`.
+    #[serde(default)]
+    pub status: String,
+    pub text: String,
+    #[serde(default)]
+    pub priority: Option<String>,
+    #[serde(default)]
+    pub created: Option<String>,
+    #[serde(default)]
+    pub start: Option<String>,
+    #[serde(default)]
+    pub scheduled: Option<String>,
+    #[serde(default)]
+    pub due: Option<String>,
+    #[serde(default)]
+    pub done: Option<String>,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub depends_on: Option<String>,
+    #[serde(default)]
+    pub tags: Option<String>,
+}
+
+impl Record {
+    pub fn from_task(section: &str, position: usize, task: &Task) -> Self {
+        let tags = task.tags();
+        Record {
+            section: section.to_string(),
+            position: Some(position),
+            status: if task.done { "done" } else { "open" }.to_string(),
+            text: task.summary(),
+            priority: task.priority().map(|p| PRIORITY_NAMES[p].to_string()),
+            created: task.field(CREATED),
+            start: task.field(START),
+            scheduled: task.field(SCHEDULED),
+            due: task.field(DUE),
+            done: task.done_date.clone(),
+            id: task.field(ID),
+            depends_on: task.field(DEPENDS_ON),
+            tags: (!tags.is_empty()).then(|| tags.join(" ")),
+        }
+    }
+
+    pub fn to_task(&self) -> Result<Task, Box<dyn std::error::Error>> {
+        let done = match self.status.trim().to_lowercase().as_str() {
+            "done" | "x" | "[x]" | "true" | "yes" => true,
+            "open" | "" | "[ ]" | "false" | "no" => false,
+            other => return Err(format!("Unknown status '{}' for '{}'", other, self.text).into()),
+        };
+        let mut task = Task {
+            text: self.text.trim().to_string(),
+            done,
+            done_date: if done { non_empty(&self.done) } else { None },
+        };
+
+        if let Some(name) = non_empty(&self.priority) {
+            let priority = PRIORITY_NAMES
+                .iter()
+                .position(|p| p.eq_ignore_ascii_case(&name))
+                .ok_or_else(|| format!("Unknown priority '{}' for '{}'", name, self.text))?;
+            task.set_priority(Some(priority));
+        }
+        for (emoji, value) in [
+            (CREATED, &self.created),
+            (START, &self.start),
+            (SCHEDULED, &self.scheduled),
+            (DUE, &self.due),
+            (ID, &self.id),
+            (DEPENDS_ON, &self.depends_on),
+        ] {
+            if let Some(value) = non_empty(value) {
+                task.set_field(emoji, &value);
+            }
+        }
+
+        Ok(task)
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+/// Every task in `files` (`(prefix, todo)` pairs from `vault::todo_files`),
+/// in file order.
+pub fn records(files: &[(String, TodoFile)]) -> Vec<Record> {
+    let mut records = Vec::new();
+    for (_, todo) in files {
+        for section in &todo.sections {
+            for (i, task) in section.tasks.iter().enumerate() {
+                records.push(Record::from_task(&section.name, i + 1, task));
+            }
+        }
+    }
+    records
+}
+
+pub fn to_csv(records: &[Record]) -> Result<String, Box<dyn std::error::Error>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for record in records {
+        writer.serialize(record)?;
+    }
+    Ok(String::from_utf8(writer.into_inner().map_err(|e| e.to_string())?)?)
+}
+
+pub fn to_json(records: &[Record]) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(serde_json::to_string_pretty(records)? + "\n")
+}
+
+pub fn from_csv(content: &str) -> Result<Vec<Record>, Box<dyn std::error::Error>> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let mut records = Vec::new();
+    for (i, row) in reader.deserialize().enumerate() {
+        // Row 1 is the header.
+        records.push(row.map_err(|e| format!("CSV row {}: {}", i + 2, e))?);
+    }
+    Ok(records)
+}
+
+pub fn from_json(content: &str) -> Result<Vec<Record>, Box<dyn std::error::Error>> {
+    Ok(serde_json::from_str(content)?)
+}
+
+/// Records grouped by section in order of first appearance, each section
+/// ordered by position. Rows without a position keep their order after
+/// the numbered ones.
+pub fn sorted(records: Vec<Record>) -> Vec<Record> {
+    let mut sections: Vec<String> = Vec::new();
+    for record in &records {
+        if !sections.contains(&record.section) {
+            sections.push(record.section.clone());
+        }
+    }
+    let mut records = records;
+    records.sort_by_key(|r| {
+        let section = sections.iter().position(|s| *s == r.section);
+        (section, r.position.is_none(), r.position)
+    });
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TODO: &str = "## Today\n- [ ] Pay rent, \"now\" #home ⏫ ➕ 2026-10-01 📅 2026-11-01\n- [x] Ship release 🆔 rel001 ✅ 2026-10-14\n\n## Next\n- [ ] Write notes ⛔ rel001 [carried:: 2]\n";
+
+    #[test]
+    fn test_records() {
+        let records = records(&[(String::new(), TodoFile::parse(TODO).unwrap())]);
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records[0],
+            Record {
+                section: "Today".to_string(),
+                position: Some(1),
+                status: "open".to_string(),
+                text: "Pay rent, \"now\" #home".to_string(),
+                priority: Some("high".to_string()),
+                created: Some("2026-10-01".to_string()),
+                due: Some("2026-11-01".to_string()),
+                tags: Some("home".to_string()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(records[1].done.as_deref(), Some("2026-10-14"));
+        assert_eq!(records[2].text, "Write notes [carried:: 2]");
+        assert_eq!(records[2].position, Some(1));
+    }
+
+    #[test]
+    fn test_csv_roundtrip() {
+        let original = records(&[(String::new(), TodoFile::parse(TODO).unwrap())]);
+        let csv = to_csv(&original).unwrap();
+        assert!(csv.starts_with("section,position,status,text,priority,"));
+        assert!(csv.contains("\"Pay rent, \"\"now\"\" #home\""));
+
+        let parsed = from_csv(&csv).unwrap();
+        assert_eq!(parsed, original);
+        assert_eq!(
+            parsed[0].to_task().unwrap().to_markdown(),
+            "- [ ] Pay rent, \"now\" #home ⏫ ➕ 2026-10-01 📅 2026-11-01"
+        );
+        assert_eq!(
+            parsed[1].to_task().unwrap().to_markdown(),
+            "- [x] Ship release 🆔 rel001 ✅ 2026-10-14"
+        );
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let original = records(&[(String::new(), TodoFile::parse(TODO).unwrap())]);
+        assert_eq!(from_json(&to_json(&original).unwrap()).unwrap(), original);
+
+        let minimal = from_json(r#"[{"section": "Inbox", "text": "Call Bob", "status": "x"}]"#).unwrap();
+        assert!(minimal[0].to_task().unwrap().done);
+    }
+
+    #[test]
+    fn test_sorted_and_errors() {
+        let rows = "section,position,status,text\nNext,2,open,B\nToday,,open,Z\nNext,1,open,A\nToday,1,open,Y\n";
+        let texts: Vec<String> = sorted(from_csv(rows).unwrap()).into_iter().map(|r| r.text).collect();
+        assert_eq!(texts, ["A", "B", "Y", "Z"]);
+
+        let bad = Record { status: "maybe".to_string(), ..Default::default() };
+        assert!(bad.to_task().is_err());
+        let bad = Record { priority: Some("urgent".to_string()), ..Default::default() };
+        assert!(bad.to_task().is_err());
+    }
+}
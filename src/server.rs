@@ -0,0 +1,395 @@
+use crate::commands;
+use crate::config::Config;
+use crate::lock;
+use crate::markdown::{Task, TodoFile};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::Read;
+use tiny_http::{Header, Response, Server};
+
+/// Request bodies larger than this are rejected.
+const MAX_BODY: u64 = 1024 * 1024;
+
+/// A task as the API returns it. `number` is the 1-based position in
+/// `section`, as in `Section:N` references.
+#[derive(Serialize, Debug)]
+struct TaskJson {
+    section: String,
+    number: Option<usize>,
+    done: bool,
+    text: String,
+    done_date: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    archived: bool,
+}
+
+impl TaskJson {
+    fn new(section: &str, number: usize, task: &Task) -> Self {
+        TaskJson {
+            section: section.to_string(),
+            number: Some(number),
+            done: task.done,
+            text: task.text.clone(),
+            done_date: task.done_date.clone(),
+            archived: false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct NewTask {
+    section: Option<String>,
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct TaskPatch {
+    done: Option<bool>,
+    text: Option<String>,
+    section: Option<String>,
+    #[serde(default)]
+    force: bool,
+}
+
+/// An HTTP status and message, returned to the client as `{"error": ...}`.
+struct ApiError(u16, String);
+
+impl From<Box<dyn std::error::Error>> for ApiError {
+    fn from(e: Box<dyn std::error::Error>) -> Self {
+        ApiError(500, e.to_string())
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+/// Serves the JSON API on `127.0.0.1:port` until the process is stopped.
+pub fn serve(config: &Config, port: u16, allow_origin: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let cors = allow_origin.map(|origin| cors_headers(&origin)).transpose()?;
+    let server = Server::http(("127.0.0.1", port)).map_err(|e| format!("Could not listen on port {}: {}", port, e))?;
+    eprintln!("Serving {} on http://{} (Ctrl-C to stop)", config.todo_path()?.display(), server.server_addr());
+    run(&server, config, cors.as_deref());
+    Ok(())
+}
+
+/// The CORS headers sent with every response when `--allow-origin` is set.
+/// Errors if `origin` is not printable ASCII, which would either be refused
+/// as a header value or split the response.
+pub fn cors_headers(origin: &str) -> Result<Vec<Header>, Box<dyn std::error::Error>> {
+    let invalid = || format!("Invalid --allow-origin value: {:?}", origin);
+    if origin.chars().any(|c| c.is_ascii_control()) {
+        return Err(invalid().into());
+    }
+    let allow_origin =
+        Header::from_bytes(&b"Access-Control-Allow-Origin"[..], origin.as_bytes()).map_err(|_| invalid())?;
+    Ok(vec![
+        allow_origin,
+        header("Access-Control-Allow-Methods", "GET, POST, PATCH, DELETE, OPTIONS"),
+        header("Access-Control-Allow-Headers", "Content-Type"),
+    ])
+}
+
+/// Answers requests on `server` one at a time, adding `cors` headers to
+/// every response.
+pub fn run(server: &Server, config: &Config, cors: Option<&[Header]>) {
+    let port = server.server_addr().to_ip().map(|addr| addr.port()).unwrap_or_default();
+    for mut request in server.incoming_requests() {
+        let method = request.method().as_str().to_string();
+        let url = request.url().to_string();
+        let header_value = |name: &str| {
+            request
+                .headers()
+                .iter()
+                .find(|h| h.field.as_str().as_str().eq_ignore_ascii_case(name))
+                .map(|h| h.value.as_str().to_string())
+        };
+        let checked = check_request(&method, header_value("Host").as_deref(), header_value("Content-Type").as_deref(), port);
+
+        let mut body = String::new();
+        let (status, value) = match checked {
+            Err(ApiError(status, message)) => (status, json!({ "error": message })),
+            Ok(()) => match request.as_reader().take(MAX_BODY + 1).read_to_string(&mut body) {
+                Ok(n) if n as u64 > MAX_BODY => (413, json!({"error": "Request body too large"})),
+                Ok(_) => handle(config, &method, &url, &body),
+                Err(e) => (400, json!({"error": format!("Could not read request body: {}", e)})),
+            },
+        };
+
+        let text = match value {
+            Value::Null => String::new(),
+            value => serde_json::to_string_pretty(&value).unwrap_or_default() + "\n",
+        };
+        let mut response = Response::from_string(text)
+            .with_status_code(status)
+            .with_header(header("Content-Type", "application/json; charset=utf-8"));
+        for cors_header in cors.unwrap_or_default() {
+            response = response.with_header(cors_header.clone());
+        }
+        if let Err(e) = request.respond(response) {
+            eprintln!("Failed to send response: {}", e);
+        }
+    }
+}
+
+/// Guards against other web pages driving the API from a browser: the
+/// `Host` must be this server (defeating DNS rebinding), and requests with a
+/// body must be `application/json`, which a cross-site form cannot send
+/// without a CORS preflight.
+fn check_request(method: &str, host: Option<&str>, content_type: Option<&str>, port: u16) -> ApiResult<()> {
+    let host_ok = host.is_some_and(|host| {
+        let (name, host_port) = match host.rsplit_once(':') {
+            Some((name, p)) => (name, p.parse::<u16>().ok()),
+            None => (host, Some(80)),
+        };
+        (name.eq_ignore_ascii_case("localhost") || name == "127.0.0.1") && host_port == Some(port)
+    });
+    if !host_ok {
+        return Err(ApiError(403, format!("Host must be localhost:{} or 127.0.0.1:{}", port, port)));
+    }
+
+    let has_body = matches!(method, "POST" | "PATCH");
+    let json = content_type.is_some_and(|ct| {
+        ct.split(';').next().is_some_and(|mime| mime.trim().eq_ignore_ascii_case("application/json"))
+    });
+    if has_body && !json {
+        return Err(ApiError(415, "Content-Type must be application/json".to_string()));
+    }
+    Ok(())
+}
+
+/// Builds a header from constant ASCII parts.
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("valid header")
+}
+
+/// Routes one request against TODO.md, holding the file lock throughout so
+/// CLI commands never interleave with it. Returns the status and JSON body.
+pub fn handle(config: &Config, method: &str, url: &str, body: &str) -> (u16, Value) {
+    match route(config, method, url, body) {
+        Ok(response) => response,
+        Err(ApiError(status, message)) => (status, json!({ "error": message })),
+    }
+}
+
+fn route(config: &Config, method: &str, url: &str, body: &str) -> ApiResult<(u16, Value)> {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments: Vec<String> = path.split('/').filter(|s| !s.is_empty()).map(percent_decode).collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+    if method == "OPTIONS" {
+        return Ok((204, Value::Null));
+    }
+
+    let todo_path = config.todo_path()?;
+    let _lock = lock::exclusive(&todo_path)?;
+    let mut todo = TodoFile::load(&todo_path)?;
+
+    let (status, value, changed) = match (method, segments.as_slice()) {
+        ("GET", ["sections"]) => (200, sections(&todo), false),
+        ("GET", ["tasks"]) => {
+            let filter = query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == "section")
+                .map(|(_, value)| percent_decode(value));
+            (200, tasks(&todo, filter.as_deref())?, false)
+        }
+        ("GET", ["tasks", section, number]) => {
+            let (s, i) = find_task(&todo, section, number)?;
+            (200, to_value(TaskJson::new(&todo.sections[s].name, i + 1, &todo.sections[s].tasks[i])), false)
+        }
+        ("POST", ["tasks"]) => {
+            let new: NewTask = parse_body(body)?;
+            let text = single_line(&new.text)?;
+            let section = new.section.unwrap_or_else(|| config.inbox_section.clone());
+            let (s, i) = commands::add_task(config, &mut todo, &section, text)?;
+            (201, to_value(TaskJson::new(&todo.sections[s].name, i + 1, &todo.sections[s].tasks[i])), true)
+        }
+        ("PATCH", ["tasks", section, number]) => {
+            let patch: TaskPatch = parse_body(body)?;
+            (200, update(config, &mut todo, section, number, patch)?, true)
+        }
+        ("DELETE", ["tasks", section, number]) => {
+            let (s, i) = find_task(&todo, section, number)?;
+            let (_, task) = commands::remove_tasks(&mut todo, s, &[i + 1])?.remove(0);
+            (200, to_value(TaskJson::new(&todo.sections[s].name, i + 1, &task)), true)
+        }
+        (_, ["sections"]) | (_, ["tasks"]) | (_, ["tasks", _, _]) => {
+            return Err(ApiError(405, format!("Method {} not allowed on {}", method, path)));
+        }
+        _ => return Err(ApiError(404, format!("No route for {}", path))),
+    };
+
+    if changed {
+        todo.save(&todo_path)?;
+    }
+    Ok((status, value))
+}
+
+fn sections(todo: &TodoFile) -> Value {
+    let sections: Vec<Value> = todo
+        .sections
+        .iter()
+        .map(|section| {
+            let tasks: Vec<TaskJson> = section
+                .tasks
+                .iter()
+                .enumerate()
+                .map(|(i, task)| TaskJson::new(&section.name, i + 1, task))
+                .collect();
+            json!({ "name": section.name, "tasks": tasks })
+        })
+        .collect();
+    Value::Array(sections)
+}
+
+fn tasks(todo: &TodoFile, section: Option<&str>) -> ApiResult<Value> {
+    if let Some(name) = section {
+        if todo.find_section(name).is_none() {
+            return Err(ApiError(404, format!("Section '{}' not found", name)));
+        }
+    }
+    let tasks: Vec<TaskJson> = todo
+        .sections
+        .iter()
+        .filter(|s| section.is_none_or(|name| s.name.eq_ignore_ascii_case(name)))
+        .flat_map(|s| s.tasks.iter().enumerate().map(|(i, task)| TaskJson::new(&s.name, i + 1, task)))
+        .collect();
+    Ok(to_value(tasks))
+}
+
+/// Applies a PATCH: new text first, then the status change, then the move,
+/// so the response describes where the task ended up.
+fn update(config: &Config, todo: &mut TodoFile, section: &str, number: &str, patch: TaskPatch) -> ApiResult<Value> {
+    let (mut s, mut i) = find_task(todo, section, number)?;
+
+    if let Some(text) = &patch.text {
+        todo.sections[s].tasks[i].text = single_line(text)?.to_string();
+    }
+
+    match patch.done {
+        Some(true) if !todo.sections[s].tasks[i].done => {
+            let name = todo.sections[s].name.clone();
+            commands::check_blockers(config, &todo.sections[s].tasks[i], &name, i + 1, patch.force)
+                .map_err(|e| ApiError(409, e.to_string()))?;
+            let task = todo.sections[s].tasks[i].clone();
+            if commands::mark_done(config, todo, s, i)? {
+                let mut json = TaskJson::new(&name, i + 1, &task);
+                json.number = None;
+                json.done = true;
+                json.done_date = Some(config.today_str()?);
+                json.archived = true;
+                return Ok(to_value(json));
+            }
+        }
+        Some(false) => commands::reopen_task(todo, s, i),
+        _ => {}
+    }
+
+    if let Some(dest) = &patch.section {
+        if !todo.sections[s].name.eq_ignore_ascii_case(dest) {
+            commands::move_tasks(todo, s, &[i + 1], dest)?;
+            s = todo.get_or_create_section(dest);
+            i = todo.sections[s].tasks.len() - 1;
+        }
+    }
+
+    Ok(to_value(TaskJson::new(&todo.sections[s].name, i + 1, &todo.sections[s].tasks[i])))
+}
+
+fn find_task(todo: &TodoFile, section: &str, number: &str) -> ApiResult<(usize, usize)> {
+    let s = todo
+        .find_section(section)
+        .ok_or_else(|| ApiError(404, format!("Section '{}' not found", section)))?;
+    let number: usize = number
+        .parse()
+        .map_err(|_| ApiError(400, format!("Invalid task number: {}", number)))?;
+    if number == 0 || number > todo.sections[s].tasks.len() {
+        return Err(ApiError(404, format!("Task {} not found in section '{}'", number, section)));
+    }
+    Ok((s, number - 1))
+}
+
+fn parse_body<T: serde::de::DeserializeOwned>(body: &str) -> ApiResult<T> {
+    serde_json::from_str(body).map_err(|e| ApiError(400, format!("Invalid JSON body: {}", e)))
+}
+
+/// Task text must be a single non-empty line to stay one Markdown list item.
+fn single_line(text: &str) -> ApiResult<&str> {
+    let text = text.trim();
+    if text.is_empty() || text.contains('\n') {
+        return Err(ApiError(400, "Task text must be a single non-empty line".to_string()));
+    }
+    Ok(text)
+}
+
+fn to_value<T: Serialize>(value: T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// Decodes `%XX` escapes and `+` in a path segment or query value.
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes.get(i..i + 3) {
+            Some([b'%', hi, lo]) => hex(*hi).zip(hex(*lo)).map(|(hi, lo)| hi * 16 + lo),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(if bytes[i] == b'+' { b' ' } else { bytes[i] });
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("Next%20Week"), "Next Week");
+        assert_eq!(percent_decode("a+b"), "a b");
+        assert_eq!(percent_decode("%E4%BB%8A%E6%97%A5"), "今日");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn test_check_request() {
+        let json = Some("application/json; charset=utf-8");
+        assert!(check_request("GET", Some("localhost:7373"), None, 7373).is_ok());
+        assert!(check_request("POST", Some("127.0.0.1:7373"), json, 7373).is_ok());
+
+        let status = |r: ApiResult<()>| r.err().map(|ApiError(status, _)| status);
+        assert_eq!(status(check_request("GET", Some("evil.example:7373"), None, 7373)), Some(403));
+        assert_eq!(status(check_request("GET", Some("localhost:8080"), None, 7373)), Some(403));
+        assert_eq!(status(check_request("GET", None, None, 7373)), Some(403));
+        assert_eq!(status(check_request("POST", Some("localhost:7373"), Some("text/plain"), 7373)), Some(415));
+        assert_eq!(status(check_request("PATCH", Some("localhost:7373"), None, 7373)), Some(415));
+    }
+
+    #[test]
+    fn test_cors_headers() {
+        assert_eq!(cors_headers("http://localhost:3000").unwrap().len(), 3);
+        assert!(cors_headers("http://a\r\nX-Evil: 1").is_err());
+        assert!(cors_headers("http://bad\u{1}").is_err());
+        assert!(cors_headers("http://例え.jp").is_err());
+    }
+
+    #[test]
+    fn test_single_line() {
+        assert_eq!(single_line("  Call Bob ").ok(), Some("Call Bob"));
+        assert!(single_line("   ").is_err());
+        assert!(single_line("one\ntwo").is_err());
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Bookkeeping that mdtodo keeps between runs, stored as TOML at
+/// `state_path`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct State {
+    /// `Config::today_str` of the last completed `rollover`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_rollover: Option<String>,
+    /// `Config::today_str` of the last completed `review`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_review: Option<String>,
+}
+
+impl State {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Ok(State::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+use crate::done_log::DoneLog;
+use crate::markdown::TodoFile;
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    Day,
+    Week,
+    Month,
+    Section,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub date: NaiveDate,
+    pub section: String,
+}
+
+/// Every completed task with a usable date: archived entries from the done
+/// log plus completed tasks still sitting in TODO.md.
+pub fn completions(todo: &TodoFile, log: &DoneLog) -> Vec<Completion> {
+    let parse = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok();
+    let mut result = Vec::new();
+
+    for day in &log.days {
+        for section in &day.sections {
+            for task in &section.tasks {
+                let date = task.done_date.as_deref().and_then(parse).or_else(|| parse(&day.date));
+                if let Some(date) = date {
+                    result.push(Completion {
+                        date,
+                        section: section.name.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    for section in &todo.sections {
+        for task in section.tasks.iter().filter(|t| t.done) {
+            if let Some(date) = task.done_date.as_deref().and_then(parse) {
+                result.push(Completion {
+                    date,
+                    section: section.name.clone(),
+                });
+            }
+        }
+    }
+
+    result
+}
+
+pub fn group_key(completion: &Completion, by: GroupBy) -> String {
+    match by {
+        GroupBy::Day => completion.date.format("%Y-%m-%d").to_string(),
+        GroupBy::Week => completion.date.format("%G-W%V").to_string(),
+        GroupBy::Month => completion.date.format("%Y-%m").to_string(),
+        GroupBy::Section => completion.section.clone(),
+    }
+}
+
+pub fn group_counts(completions: &[Completion], by: GroupBy) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for completion in completions {
+        *counts.entry(group_key(completion, by)).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Current and longest runs of consecutive days with at least one
+/// completion. The current streak may end yesterday, so it does not reset
+/// before the first task of the day is done.
+pub fn streaks(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> (usize, usize) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut prev: Option<NaiveDate> = None;
+
+    for &day in days {
+        run = match prev {
+            Some(p) if p + Duration::days(1) == day => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        prev = Some(day);
+    }
+
+    let mut current = 0;
+    let mut day = if days.contains(&today) {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    while days.contains(&day) {
+        current += 1;
+        day -= Duration::days(1);
+    }
+
+    (current, longest)
+}
+
+fn shade(count: usize) -> char {
+    match count {
+        0 => '·',
+        1 => '░',
+        2..=3 => '▒',
+        4..=5 => '▓',
+        _ => '█',
+    }
+}
+
+/// A weekday × week grid of completions, ending with the week of `until`.
+pub fn heatmap(per_day: &BTreeMap<NaiveDate, usize>, until: NaiveDate, weeks: i64) -> String {
+    let last_monday = until - Duration::days(until.weekday().num_days_from_monday() as i64);
+    let first_monday = last_monday - Duration::weeks(weeks - 1);
+
+    let mut out = format!("     {}\n", first_monday.format("%Y-%m-%d"));
+    for (row, label) in ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"].iter().enumerate() {
+        out.push_str(label);
+        out.push_str("  ");
+        for week in 0..weeks {
+            let day = first_monday + Duration::weeks(week) + Duration::days(row as i64);
+            if day > until {
+                out.push(' ');
+            } else {
+                out.push(shade(per_day.get(&day).copied().unwrap_or(0)));
+            }
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_completions_and_grouping() {
+        let todo = TodoFile::parse("## Today\n- [x] A ✅ 2026-10-16\n- [ ] B\n").unwrap();
+        let log = DoneLog::parse(
+            "# Done Log\n\n## 2026-10-15\n\n### Next\n- [x] C ✅ 2026-10-15\n- [x] D\n\n## 2026-09-30\n\n### Today\n- [x] E ✅ 2026-09-30\n",
+        );
+
+        let completions = completions(&todo, &log);
+        assert_eq!(completions.len(), 4);
+
+        let by_month = group_counts(&completions, GroupBy::Month);
+        assert_eq!(by_month.get("2026-10"), Some(&3));
+        assert_eq!(by_month.get("2026-09"), Some(&1));
+
+        let by_section = group_counts(&completions, GroupBy::Section);
+        assert_eq!(by_section.get("Next"), Some(&2));
+        assert_eq!(by_section.get("Today"), Some(&2));
+
+        let by_week = group_counts(&completions, GroupBy::Week);
+        assert_eq!(by_week.get("2026-W42"), Some(&3));
+    }
+
+    #[test]
+    fn test_streaks() {
+        let days: BTreeSet<NaiveDate> = [
+            date(2026, 10, 1),
+            date(2026, 10, 2),
+            date(2026, 10, 3),
+            date(2026, 10, 15),
+            date(2026, 10, 16),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(streaks(&days, date(2026, 10, 17)), (2, 3));
+        assert_eq!(streaks(&days, date(2026, 10, 16)), (2, 3));
+        assert_eq!(streaks(&days, date(2026, 10, 20)), (0, 3));
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let mut per_day = BTreeMap::new();
+        per_day.insert(date(2026, 10, 12), 1);
+        per_day.insert(date(2026, 10, 14), 6);
+
+        // 2026-10-14 is a Wednesday
+        let map = heatmap(&per_day, date(2026, 10, 14), 2);
+        let lines: Vec<&str> = map.lines().collect();
+        assert_eq!(lines[0], "     2026-10-05");
+        assert_eq!(lines[1], "Mon  ·░");
+        assert_eq!(lines[3], "Wed  ·█");
+        assert_eq!(lines[4], "Thu  · ");
+    }
+}
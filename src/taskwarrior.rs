@@ -0,0 +1,127 @@
+use crate::config::Config;
+use crate::import::{ImportedTask, SOURCE};
+use crate::markdown::{Task, CREATED, DUE, PRIORITIES, SCHEDULED, START};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Deserialize;
+
+/// One entry of `task export`. Only the fields mdtodo keeps are read.
+#[derive(Deserialize, Debug)]
+struct Entry {
+    uuid: String,
+    description: String,
+    #[serde(default)]
+    status: String,
+    project: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    priority: Option<String>,
+    entry: Option<String>,
+    wait: Option<String>,
+    scheduled: Option<String>,
+    due: Option<String>,
+    end: Option<String>,
+}
+
+/// Tasks in a `task export` JSON array. The project becomes the section,
+/// tags become `#tags`, H/M/L become ⏫/🔼/🔽, `entry` / `wait` /
+/// `scheduled` / `due` become ➕ / 🛫 / ⏳ / 📅, and the uuid is kept as
+/// `[source:: taskwarrior:<uuid>]`. Deleted tasks and recurrence templates
+/// are skipped.
+pub fn parse(config: &Config, content: &str) -> Result<Vec<ImportedTask>, Box<dyn std::error::Error>> {
+    let entries: Vec<Entry> = serde_json::from_str(content)?;
+    // Dates are stored in UTC (`20261020T150000Z`); a due date of "tomorrow"
+    // means local midnight, so convert back before dropping the time.
+    let local_date = |value: Option<&str>| -> Result<Option<NaiveDate>, Box<dyn std::error::Error>> {
+        match value.and_then(|v| NaiveDateTime::parse_from_str(v, "%Y%m%dT%H%M%SZ").ok()) {
+            Some(dt) => Ok(Some(config.local_date(dt.and_utc())?)),
+            None => Ok(None),
+        }
+    };
+    let mut tasks = Vec::new();
+
+    for entry in entries {
+        if entry.status == "deleted" || entry.status == "recurring" {
+            continue;
+        }
+
+        let mut text = entry.description.trim().to_string();
+        for tag in &entry.tags {
+            text.push_str(&format!(" #{}", tag));
+        }
+        let done = entry.status == "completed";
+        let mut task = Task {
+            text,
+            done,
+            done_date: if done { local_date(entry.end.as_deref())?.map(|d| d.to_string()) } else { None },
+        };
+
+        let priority = match entry.priority.as_deref() {
+            Some("H") => Some(1),
+            Some("M") => Some(2),
+            Some("L") => Some(3),
+            _ => None,
+        };
+        task.set_priority(priority.filter(|p| *p < PRIORITIES.len()));
+        for (emoji, value) in [(CREATED, &entry.entry), (START, &entry.wait), (SCHEDULED, &entry.scheduled), (DUE, &entry.due)] {
+            if let Some(date) = local_date(value.as_deref())? {
+                task.set_field(emoji, &date.to_string());
+            }
+        }
+        task.set_inline_field(SOURCE, &format!("taskwarrior:{}", entry.uuid));
+
+        tasks.push(ImportedTask::new(entry.project.filter(|p| !p.is_empty()), task));
+    }
+
+    Ok(tasks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc() -> Config {
+        Config {
+            timezone: "UTC".to_string(),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let json = r#"[
+            {"id": 1, "uuid": "a1b2", "description": "Fix the gate", "status": "pending", "project": "Home",
+             "tags": ["garden", "weekend"], "priority": "H", "entry": "20261001T120000Z", "due": "20261020T120000Z", "urgency": 9.1},
+            {"id": 0, "uuid": "c3d4", "description": "File taxes", "status": "completed", "end": "20261015T120000Z"},
+            {"id": 0, "uuid": "e5f6", "description": "Gone", "status": "deleted"}
+        ]"#;
+        let tasks = parse(&utc(), json).unwrap();
+        assert_eq!(tasks.len(), 2);
+
+        assert_eq!(tasks[0].section.as_deref(), Some("Home"));
+        assert_eq!(
+            tasks[0].task.to_markdown(),
+            "- [ ] Fix the gate #garden #weekend ⏫ ➕ 2026-10-01 📅 2026-10-20 [source:: taskwarrior:a1b2]"
+        );
+        assert_eq!(tasks[1].section, None);
+        assert_eq!(
+            tasks[1].task.to_markdown(),
+            "- [x] File taxes [source:: taskwarrior:c3d4] ✅ 2026-10-15"
+        );
+    }
+
+    #[test]
+    fn test_dates_use_configured_timezone() {
+        let json = r#"[{"uuid": "a1b2", "description": "Call", "status": "pending", "due": "20261019T150000Z"}]"#;
+        let config = Config {
+            timezone: "Asia/Tokyo".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(parse(&config, json).unwrap()[0].task.field(DUE).as_deref(), Some("2026-10-20"));
+
+        let config = Config {
+            timezone: "America/New_York".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(parse(&config, json).unwrap()[0].task.field(DUE).as_deref(), Some("2026-10-19"));
+    }
+}
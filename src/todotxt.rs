@@ -0,0 +1,215 @@
+use crate::markdown::{Task, TodoFile, CREATED, DUE, PRIORITIES, SCHEDULED};
+use regex::Regex;
+
+/// todo.txt priorities matching `PRIORITIES`, highest first.
+const LETTERS: [char; 5] = ['A', 'B', 'C', 'D', 'E'];
+
+/// One todo.txt line per task. The section becomes a `+project` (spaces as
+/// `_`), `#tags` become `@contexts`, and 📅 / ⏳ become `due:` / `t:`.
+pub fn export(files: &[(String, TodoFile)]) -> String {
+    let mut out = String::new();
+    for (_, todo) in files {
+        for section in &todo.sections {
+            for task in &section.tasks {
+                out.push_str(&to_line(task, &section.name));
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+pub fn to_line(task: &Task, section: &str) -> String {
+    let created = task.field(CREATED);
+    let due = task.field(DUE);
+    let scheduled = task.field(SCHEDULED);
+    let priority = task.priority().map(|p| LETTERS[p]);
+
+    let mut rest = task.clone();
+    for emoji in [CREATED, DUE, SCHEDULED] {
+        rest.remove_field(emoji);
+    }
+    rest.set_priority(None);
+    let text = escape_first_word(&tag_regex().replace_all(rest.text.trim(), "$1@$2"));
+
+    let mut parts: Vec<String> = Vec::new();
+    if task.done {
+        parts.push("x".to_string());
+        // A creation date is only allowed after a completion date.
+        if let Some(done_date) = &task.done_date {
+            parts.push(done_date.clone());
+            parts.extend(created);
+        }
+    } else {
+        parts.extend(priority.map(|p| format!("({})", p)));
+        parts.extend(created);
+    }
+    parts.push(text);
+    parts.push(format!("+{}", section.replace(' ', "_")));
+    parts.extend(due.map(|d| format!("due:{}", d)));
+    parts.extend(scheduled.map(|d| format!("t:{}", d)));
+    if task.done {
+        parts.extend(priority.map(|p| format!("pri:{}", p)));
+    }
+
+    parts.join(" ")
+}
+
+/// Text whose first word would read as a completion mark, priority or
+/// date (or already starts with `\` before one) gets a leading `\`, which
+/// `from_line` strips again.
+fn escape_first_word(text: &str) -> String {
+    match text.split_whitespace().next() {
+        Some(word) if is_marker(word.trim_start_matches('\\')) => format!("\\{}", text),
+        _ => text.to_string(),
+    }
+}
+
+/// Whether `word` would be taken as part of the `x` / `(A)` / date prefix.
+fn is_marker(word: &str) -> bool {
+    let date = Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap();
+    let priority = Regex::new(r"^\([A-Z]\)$").unwrap();
+    word == "x" || priority.is_match(word) || date.is_match(word)
+}
+
+/// Tasks in a todo.txt file with the `+project` each was filed under.
+pub fn parse(content: &str) -> Vec<(Option<String>, Task)> {
+    content.lines().filter_map(from_line).collect()
+}
+
+/// Reads one todo.txt line. The last `+project` is taken as the section,
+/// since `to_line` writes it after the text; earlier projects stay in the
+/// text.
+pub fn from_line(line: &str) -> Option<(Option<String>, Task)> {
+    let date = Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap();
+    let mut tokens = line.split_whitespace().peekable();
+    tokens.peek()?;
+
+    let done = tokens.next_if(|t| *t == "x").is_some();
+    let mut done_date = None;
+    let mut created = None;
+    let mut priority = None;
+
+    if done {
+        done_date = tokens.next_if(|t| date.is_match(t)).map(str::to_string);
+        if done_date.is_some() {
+            created = tokens.next_if(|t| date.is_match(t)).map(str::to_string);
+        }
+    } else {
+        priority = tokens
+            .next_if(|t| t.len() == 3 && t.starts_with('(') && t.ends_with(')'))
+            .and_then(|t| t.chars().nth(1))
+            .filter(char::is_ascii_uppercase);
+        created = tokens.next_if(|t| date.is_match(t)).map(str::to_string);
+    }
+
+    let mut tokens: Vec<&str> = tokens.collect();
+    let project_name = |t: &str| t.strip_prefix('+').filter(|n| !n.is_empty()).map(|n| n.replace('_', " "));
+    let project = tokens
+        .iter()
+        .rposition(|t| project_name(t).is_some())
+        .and_then(|i| project_name(tokens.remove(i)));
+    if let Some(first) = tokens.first_mut() {
+        if let Some(word) = first.strip_prefix('\\').filter(|w| is_marker(w.trim_start_matches('\\'))) {
+            *first = word;
+        }
+    }
+
+    let mut due = None;
+    let mut scheduled = None;
+    let mut words = Vec::new();
+    for token in tokens {
+        if let Some(context) = token.strip_prefix('@').filter(|c| !c.is_empty()) {
+            words.push(format!("#{}", context));
+            continue;
+        } else if let Some(value) = token.strip_prefix("due:").filter(|v| date.is_match(v)) {
+            due = Some(value.to_string());
+            continue;
+        } else if let Some(value) = token.strip_prefix("t:").filter(|v| date.is_match(v)) {
+            scheduled = Some(value.to_string());
+            continue;
+        } else if let Some(value) = token.strip_prefix("pri:") {
+            priority = value.chars().next().filter(char::is_ascii_uppercase);
+            continue;
+        }
+        words.push(token.to_string());
+    }
+
+    let mut task = Task {
+        text: words.join(" "),
+        done,
+        done_date,
+    };
+    if let Some(letter) = priority {
+        let index = LETTERS.iter().position(|l| *l == letter).unwrap_or(PRIORITIES.len() - 1);
+        task.set_priority(Some(index));
+    }
+    if let Some(created) = created {
+        task.set_field(CREATED, &created);
+    }
+    if let Some(scheduled) = scheduled {
+        task.set_field(SCHEDULED, &scheduled);
+    }
+    if let Some(due) = due {
+        task.set_field(DUE, &due);
+    }
+    task.text = task.text.trim().to_string();
+
+    Some((project, task))
+}
+
+fn tag_regex() -> Regex {
+    Regex::new(r"(^|\s)#([^\s#\[\]]+)").unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_line() {
+        let task = Task::from_line("- [ ] Call Bob #phone ⏫ ➕ 2026-10-01 📅 2026-10-20").unwrap();
+        assert_eq!(
+            to_line(&task, "Next Week"),
+            "(B) 2026-10-01 Call Bob @phone +Next_Week due:2026-10-20"
+        );
+
+        let done = Task::from_line("- [x] Ship 🔺 ➕ 2026-10-01 ✅ 2026-10-17").unwrap();
+        assert_eq!(to_line(&done, "Today"), "x 2026-10-17 2026-10-01 Ship +Today pri:A");
+    }
+
+    #[test]
+    fn test_from_line() {
+        let (project, task) =
+            from_line("(A) 2026-10-01 Review +Work +Legal contract @office due:2026-10-20 t:2026-10-18").unwrap();
+        assert_eq!(project.as_deref(), Some("Legal"));
+        assert_eq!(task.text, "Review +Work contract #office 🔺 ➕ 2026-10-01 ⏳ 2026-10-18 📅 2026-10-20");
+        assert!(!task.done);
+
+        let (project, task) = from_line("x 2026-10-17 Buy milk").unwrap();
+        assert_eq!(project, None);
+        assert!(task.done);
+        assert_eq!(task.done_date.as_deref(), Some("2026-10-17"));
+        assert_eq!(task.text, "Buy milk");
+
+        assert!(from_line("   ").is_none());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let todo = TodoFile::parse(
+            "## Today\n- [ ] Call Bob #phone ⏫ ➕ 2026-10-01 ⏳ 2026-10-18 📅 2026-10-20\n- [x] Ship release 🔽 ➕ 2026-09-30 ✅ 2026-10-17\n- [ ] Plain task\n- [ ] x marks the spot\n- [ ] Review +Legal contract\n- [ ] (A) is not a priority ⏬\n- [x] 2026-10-20 standup ✅ 2026-10-20\n\n## Some Day\n- [ ] Learn #music/piano\n- [ ] \\x stays as typed\n",
+        )
+        .unwrap();
+        let expected: Vec<(Option<String>, Task)> = todo
+            .sections
+            .iter()
+            .flat_map(|s| s.tasks.iter().map(move |t| (Some(s.name.clone()), t.clone())))
+            .collect();
+
+        let exported = export(&[(String::new(), todo)]);
+        assert!(exported.contains("\n\\x marks the spot +Today\n"));
+        assert!(exported.contains("\nReview +Legal contract +Today\n"));
+        assert_eq!(parse(&exported), expected);
+    }
+}
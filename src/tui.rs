@@ -0,0 +1,610 @@
+use crate::commands;
+use crate::config::Config;
+use crate::lock::{self, FileLock};
+use crate::markdown::{Task, TodoFile};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// How often the event loop wakes up to check TODO.md for outside edits.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mode {
+    Normal,
+    Move { target: usize },
+    Edit(String),
+    Add(String),
+    ConfirmDelete,
+}
+
+/// Full-screen state. Every change goes through `TodoFile` and is saved
+/// immediately, just like a CLI command.
+pub struct App {
+    config: Config,
+    todo_path: PathBuf,
+    pub todo: TodoFile,
+    pub section: usize,
+    pub task: usize,
+    pub mode: Mode,
+    pub message: String,
+    modified: Option<SystemTime>,
+    pub quit: bool,
+}
+
+pub fn run(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let mut app = App::load(config)?;
+    let mut terminal = ratatui::init();
+    let result = app.event_loop(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl App {
+    pub fn load(config: &Config) -> Result<Self, Box<dyn std::error::Error>> {
+        let todo_path = config.todo_path()?;
+        let mut app = App {
+            config: config.clone(),
+            todo: TodoFile::load(&todo_path)?,
+            todo_path,
+            section: 0,
+            task: 0,
+            mode: Mode::Normal,
+            message: String::new(),
+            modified: None,
+            quit: false,
+        };
+        app.modified = app.disk_mtime();
+        Ok(app)
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<(), Box<dyn std::error::Error>> {
+        while !self.quit {
+            if self.mode == Mode::Normal {
+                self.reload_if_changed()?;
+            }
+
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if !event::poll(POLL_INTERVAL)? {
+                continue;
+            }
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                if let Err(e) = self.handle_key(key.code) {
+                    self.mode = Mode::Normal;
+                    self.message = format!("Error: {}", e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn disk_mtime(&self) -> Option<SystemTime> {
+        fs::metadata(&self.todo_path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Picks up edits made by other programs (Obsidian, the CLI) since the
+    /// last load or save.
+    pub fn reload_if_changed(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.disk_mtime() != self.modified {
+            self.reload()?;
+            self.message = "Reloaded: file changed on disk".to_string();
+        }
+        Ok(())
+    }
+
+    fn reload(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.todo = TodoFile::load(&self.todo_path)?;
+        self.modified = self.disk_mtime();
+        self.clamp();
+        Ok(())
+    }
+
+    /// Takes the TODO.md lock before a change. If the file changed on disk
+    /// since the last load or save, it is reloaded first and the same section
+    /// (and, with `keep_task`, the same task) is selected again so the change
+    /// lands on the fresh copy. Returns `None` when that target is gone, in
+    /// which case nothing should be changed.
+    fn lock_fresh(&mut self, keep_task: bool) -> Result<Option<FileLock>, Box<dyn std::error::Error>> {
+        let lock = lock::exclusive(&self.todo_path)?;
+        if self.disk_mtime() == self.modified {
+            return Ok(Some(lock));
+        }
+
+        let section = self.todo.sections.get(self.section).map(|s| s.name.clone());
+        let task = self.selected_task().cloned();
+        self.reload()?;
+
+        let found = section.and_then(|name| self.todo.find_section(&name)).and_then(|section| {
+            self.section = section;
+            match &task {
+                Some(task) if keep_task => self.current_tasks().iter().position(|t| t == task),
+                _ => Some(self.task),
+            }
+        });
+        match found {
+            Some(task) => {
+                self.task = task;
+                self.clamp();
+                Ok(Some(lock))
+            }
+            None => {
+                self.clamp();
+                self.message = "TODO.md changed on disk and the selection is gone; reloaded without applying the change".to_string();
+                Ok(None)
+            }
+        }
+    }
+
+    /// Saves while the caller holds the lock from `lock_fresh`.
+    fn save(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.todo.save(&self.todo_path)?;
+        self.modified = self.disk_mtime();
+        Ok(())
+    }
+
+    fn clamp(&mut self) {
+        self.section = self.section.min(self.todo.sections.len().saturating_sub(1));
+        let tasks = self.current_tasks().len();
+        self.task = self.task.min(tasks.saturating_sub(1));
+    }
+
+    fn current_tasks(&self) -> &[Task] {
+        self.todo
+            .sections
+            .get(self.section)
+            .map(|s| s.tasks.as_slice())
+            .unwrap_or(&[])
+    }
+
+    fn selected_task(&self) -> Option<&Task> {
+        self.current_tasks().get(self.task)
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> Result<(), Box<dyn std::error::Error>> {
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => self.handle_normal_key(key),
+            Mode::Move { target } => self.handle_move_key(key, target),
+            Mode::Edit(buffer) => self.handle_input_key(key, buffer, false),
+            Mode::Add(buffer) => self.handle_input_key(key, buffer, true),
+            Mode::ConfirmDelete => {
+                if key == KeyCode::Char('y') {
+                    self.delete_selected()?;
+                } else {
+                    self.message = "Delete cancelled".to_string();
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn handle_normal_key(&mut self, key: KeyCode) -> Result<(), Box<dyn std::error::Error>> {
+        self.message.clear();
+        let section_count = self.todo.sections.len();
+
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.section = self.section.saturating_sub(1);
+                self.task = 0;
+            }
+            KeyCode::Right | KeyCode::Char('l') if self.section + 1 < section_count => {
+                self.section += 1;
+                self.task = 0;
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.task = self.task.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') if self.task + 1 < self.current_tasks().len() => {
+                self.task += 1;
+            }
+            KeyCode::Char(' ') | KeyCode::Char('x') => self.toggle_selected()?,
+            KeyCode::Char('m') if self.selected_task().is_some() => {
+                self.mode = Mode::Move {
+                    target: self.section,
+                };
+            }
+            KeyCode::Char('e') => {
+                if let Some(task) = self.selected_task() {
+                    self.mode = Mode::Edit(task.text.clone());
+                }
+            }
+            KeyCode::Char('a') if section_count > 0 => self.mode = Mode::Add(String::new()),
+            KeyCode::Char('d') if self.selected_task().is_some() => {
+                self.mode = Mode::ConfirmDelete;
+            }
+            KeyCode::Char('A') => self.archive_section()?,
+            KeyCode::Char('r') => {
+                self.reload()?;
+                self.message = "Reloaded".to_string();
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn handle_move_key(&mut self, key: KeyCode, target: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let last = self.todo.sections.len().saturating_sub(1);
+        match key {
+            KeyCode::Left | KeyCode::Up | KeyCode::Char('h') | KeyCode::Char('k') => {
+                self.mode = Mode::Move {
+                    target: target.saturating_sub(1),
+                };
+            }
+            KeyCode::Right | KeyCode::Down | KeyCode::Char('l') | KeyCode::Char('j') => {
+                self.mode = Mode::Move {
+                    target: (target + 1).min(last),
+                };
+            }
+            KeyCode::Enter => self.move_selected(target)?,
+            KeyCode::Esc => self.message = "Move cancelled".to_string(),
+            _ => self.mode = Mode::Move { target },
+        }
+        Ok(())
+    }
+
+    fn handle_input_key(
+        &mut self,
+        key: KeyCode,
+        mut buffer: String,
+        adding: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match key {
+            KeyCode::Enter => {
+                let text = buffer.trim().to_string();
+                if text.is_empty() {
+                    return Ok(());
+                }
+                if adding {
+                    self.add_task(text)?;
+                } else {
+                    self.edit_selected(text)?;
+                }
+                return Ok(());
+            }
+            KeyCode::Esc => return Ok(()),
+            KeyCode::Backspace => {
+                buffer.pop();
+            }
+            KeyCode::Char(c) => buffer.push(c),
+            _ => {}
+        }
+
+        self.mode = if adding {
+            Mode::Add(buffer)
+        } else {
+            Mode::Edit(buffer)
+        };
+        Ok(())
+    }
+
+    /// Completes or reopens the selected task the way `done` does: blockers
+    /// are looked up across the vault and `auto_archive = "on_done"` applies.
+    fn toggle_selected(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let _lock = match self.lock_fresh(true)? {
+            Some(lock) => lock,
+            None => return Ok(()),
+        };
+        let task = match self.selected_task() {
+            Some(task) => task.clone(),
+            None => return Ok(()),
+        };
+
+        if task.done {
+            let task = &mut self.todo.sections[self.section].tasks[self.task];
+            task.done = false;
+            task.done_date = None;
+            self.message = format!("Marked as undone: {}", task.text);
+            return self.save();
+        }
+
+        let section = self.todo.sections[self.section].name.clone();
+        let blockers = match commands::check_blockers(&self.config, &task, &section, self.task + 1, false) {
+            Ok(blockers) => blockers,
+            Err(e) => {
+                self.message = e.to_string();
+                return Ok(());
+            }
+        };
+        self.message = if commands::mark_done(&self.config, &mut self.todo, self.section, self.task)? {
+            format!("Marked as done and archived: {}", task.text)
+        } else {
+            format!("Marked as done: {}", task.text)
+        };
+        if !blockers.is_empty() {
+            self.message = format!("{} (⚠ blocked by {})", self.message, blockers.join(", "));
+        }
+        self.clamp();
+        self.save()
+    }
+
+    fn move_selected(&mut self, target: usize) -> Result<(), Box<dyn std::error::Error>> {
+        if target == self.section || self.selected_task().is_none() {
+            return Ok(());
+        }
+        let target_name = self.todo.sections[target].name.clone();
+        let _lock = match self.lock_fresh(true)? {
+            Some(lock) => lock,
+            None => return Ok(()),
+        };
+        let target = match self.todo.find_section(&target_name) {
+            Some(target) if target != self.section => target,
+            _ => return Ok(()),
+        };
+
+        let task = self.todo.sections[self.section].tasks.remove(self.task);
+        self.message = format!(
+            "Moved: {} ({} -> {})",
+            task.text, self.todo.sections[self.section].name, self.todo.sections[target].name
+        );
+        self.todo.sections[target].tasks.push(task);
+        self.clamp();
+        self.save()
+    }
+
+    fn edit_selected(&mut self, text: String) -> Result<(), Box<dyn std::error::Error>> {
+        let _lock = match self.lock_fresh(true)? {
+            Some(lock) => lock,
+            None => {
+                // Keep the typed text so it can be re-applied elsewhere.
+                self.mode = Mode::Edit(text);
+                return Ok(());
+            }
+        };
+        if let Some(task) = self
+            .todo
+            .sections
+            .get_mut(self.section)
+            .and_then(|s| s.tasks.get_mut(self.task))
+        {
+            task.text = text;
+            self.message = format!("Edited: {}", task.text);
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn add_task(&mut self, text: String) -> Result<(), Box<dyn std::error::Error>> {
+        let _lock = match self.lock_fresh(false)? {
+            Some(lock) => lock,
+            None => {
+                self.mode = Mode::Add(text);
+                return Ok(());
+            }
+        };
+        let task = commands::new_task(&self.config, &text)?;
+        let section = &mut self.todo.sections[self.section];
+        section.tasks.push(task);
+        self.task = section.tasks.len() - 1;
+        self.message = format!("Added to {}: {}", section.name, text);
+        self.save()
+    }
+
+    fn delete_selected(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let _lock = match self.lock_fresh(true)? {
+            Some(lock) => lock,
+            None => return Ok(()),
+        };
+        if self.selected_task().is_none() {
+            return Ok(());
+        }
+        let task = self.todo.sections[self.section].tasks.remove(self.task);
+        self.message = format!("Deleted: {}", task.text);
+        self.clamp();
+        self.save()
+    }
+
+    fn archive_section(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let _lock = match self.lock_fresh(false)? {
+            Some(lock) => lock,
+            None => return Ok(()),
+        };
+        let section = match self.todo.sections.get(self.section) {
+            Some(section) => section.name.clone(),
+            None => return Ok(()),
+        };
+
+        let archived = commands::archive_tasks(&self.config, &mut self.todo, self.section, "all")?;
+        if archived.is_empty() {
+            self.message = format!("No completed tasks to archive in section '{}'", section);
+            return Ok(());
+        }
+
+        self.message = format!("Archived {} task(s) from {}", archived.len(), section);
+        self.clamp();
+        self.save()
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(2)]).areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Length(28), Constraint::Min(1)]).areas(main);
+
+        let highlighted = match self.mode {
+            Mode::Move { target } => target,
+            _ => self.section,
+        };
+
+        let sections: Vec<ListItem> = self
+            .todo
+            .sections
+            .iter()
+            .map(|s| {
+                let open = s.tasks.iter().filter(|t| !t.done).count();
+                ListItem::new(format!("{} ({}/{})", s.name, open, s.tasks.len()))
+            })
+            .collect();
+        let sections_title = match self.mode {
+            Mode::Move { .. } => " Move to… ",
+            _ => " Sections ",
+        };
+        let sections = List::new(sections)
+            .block(Block::default().borders(Borders::ALL).title(sections_title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut section_state = ListState::default().with_selected(Some(highlighted));
+        frame.render_stateful_widget(sections, left, &mut section_state);
+
+        let tasks: Vec<ListItem> = self
+            .current_tasks()
+            .iter()
+            .enumerate()
+            .map(|(idx, task)| {
+                let line = format!("{:>3}: {}", idx + 1, task.to_markdown().trim_start_matches("- "));
+                let style = if task.done {
+                    Style::default()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::CROSSED_OUT)
+                } else {
+                    Style::default()
+                };
+                ListItem::new(Line::styled(line, style))
+            })
+            .collect();
+        let tasks_title = format!(
+            " {} — {} ",
+            self.todo
+                .sections
+                .get(self.section)
+                .map(|s| s.name.as_str())
+                .unwrap_or("(no sections)"),
+            self.todo_path.display()
+        );
+        let tasks = List::new(tasks)
+            .block(Block::default().borders(Borders::ALL).title(tasks_title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut task_state = ListState::default().with_selected(Some(self.task));
+        frame.render_stateful_widget(tasks, right, &mut task_state);
+
+        let (first, hints) = match &self.mode {
+            Mode::Normal => (
+                self.message.clone(),
+                "←/→ section  ↑/↓ task  space done  m move  e edit  a add  d delete  A archive  r reload  q quit",
+            ),
+            Mode::Move { .. } => (self.message.clone(), "↑/↓ choose section  Enter move  Esc cancel"),
+            Mode::Edit(buffer) => (format!("Edit: {}▏", buffer), "Enter save  Esc cancel"),
+            Mode::Add(buffer) => (format!("Add: {}▏", buffer), "Enter add  Esc cancel"),
+            Mode::ConfirmDelete => ("Delete selected task? (y/N)".to_string(), ""),
+        };
+        let status_text = vec![
+            Line::from(first),
+            Line::styled(hints, Style::default().fg(Color::DarkGray)),
+        ];
+        frame.render_widget(Paragraph::new(status_text), status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AutoArchive, BlockedDone};
+    use tempfile::TempDir;
+
+    fn app_with(content: &str) -> (TempDir, App) {
+        let temp = TempDir::new().unwrap();
+        let todo_path = temp.path().join("TODO.md");
+        fs::write(&todo_path, content).unwrap();
+        let config = Config {
+            todo_path: todo_path.to_string_lossy().to_string(),
+            done_path: temp.path().join("done_list.md").to_string_lossy().to_string(),
+            ..Config::default()
+        };
+        let app = App::load(&config).unwrap();
+        (temp, app)
+    }
+
+    fn press(app: &mut App, keys: &[KeyCode]) {
+        for key in keys {
+            app.handle_key(*key).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_toggle_and_move_are_saved() {
+        let (temp, mut app) = app_with("# TODO\n\n## Today\n- [ ] A\n- [ ] B\n\n## Next\n");
+
+        press(&mut app, &[KeyCode::Down, KeyCode::Char(' ')]);
+        press(&mut app, &[KeyCode::Char('m'), KeyCode::Down, KeyCode::Enter]);
+
+        let saved = TodoFile::load(&temp.path().join("TODO.md")).unwrap();
+        assert_eq!(saved.sections[0].tasks.len(), 1);
+        assert_eq!(saved.sections[1].tasks[0].text, "B");
+        assert!(saved.sections[1].tasks[0].done);
+    }
+
+    #[test]
+    fn test_inline_edit_and_add() {
+        let (temp, mut app) = app_with("# TODO\n\n## Today\n- [ ] Draft\n");
+
+        press(&mut app, &[KeyCode::Char('e'), KeyCode::Char('!'), KeyCode::Enter]);
+        press(&mut app, &[KeyCode::Char('a')]);
+        for c in "New".chars() {
+            press(&mut app, &[KeyCode::Char(c)]);
+        }
+        press(&mut app, &[KeyCode::Enter]);
+
+        let saved = TodoFile::load(&temp.path().join("TODO.md")).unwrap();
+        let texts: Vec<&str> = saved.sections[0].tasks.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(texts, vec!["Draft!", "New"]);
+        assert_eq!(app.mode, Mode::Normal);
+    }
+
+    #[test]
+    fn test_reload_after_external_change() {
+        let (temp, mut app) = app_with("# TODO\n\n## Today\n- [ ] A\n");
+
+        fs::write(temp.path().join("TODO.md"), "# TODO\n\n## Today\n- [ ] A\n- [ ] B\n").unwrap();
+        app.modified = None;
+        app.reload_if_changed().unwrap();
+
+        assert_eq!(app.todo.sections[0].tasks.len(), 2);
+    }
+
+    #[test]
+    fn test_edit_is_applied_to_the_file_on_disk() {
+        let (temp, mut app) = app_with("# TODO\n\n## Today\n- [ ] A\n");
+        let path = temp.path().join("TODO.md");
+
+        press(&mut app, &[KeyCode::Char('e'), KeyCode::Char('!')]);
+        fs::write(&path, "# TODO\n\n## Today\n- [ ] B\n- [ ] A\n").unwrap();
+        app.modified = None;
+        press(&mut app, &[KeyCode::Enter]);
+        let saved = TodoFile::load(&path).unwrap();
+        let texts: Vec<&str> = saved.sections[0].tasks.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(texts, vec!["B", "A!"]);
+
+        press(&mut app, &[KeyCode::Char('e'), KeyCode::Char('?')]);
+        fs::write(&path, "# TODO\n\n## Today\n- [ ] B\n").unwrap();
+        app.modified = None;
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# TODO\n\n## Today\n- [ ] B\n");
+        assert_eq!(app.mode, Mode::Edit("A!?".to_string()));
+    }
+
+    #[test]
+    fn test_toggle_follows_done_rules() {
+        let (temp, mut app) = app_with("# TODO\n\n## Today\n- [ ] Ship ⛔ spec01\n- [ ] Tidy\n");
+        fs::write(temp.path().join("spec.md"), "## Spec\n- [ ] Write spec 🆔 spec01\n").unwrap();
+        app.config.vault_path = Some(temp.path().to_string_lossy().to_string());
+        app.config.blocked_done = BlockedDone::Refuse;
+        app.config.auto_archive = AutoArchive::OnDone;
+
+        press(&mut app, &[KeyCode::Char('x')]);
+        assert!(app.message.contains("blocked by open task(s): spec01"));
+        assert!(!app.todo.sections[0].tasks[0].done);
+
+        press(&mut app, &[KeyCode::Down, KeyCode::Char('x')]);
+        let saved = TodoFile::load(&temp.path().join("TODO.md")).unwrap();
+        assert_eq!(saved.sections[0].tasks.len(), 1);
+        assert!(fs::read_to_string(temp.path().join("done_list.md")).unwrap().contains("Tidy"));
+    }
+}
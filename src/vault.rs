@@ -0,0 +1,164 @@
+use crate::config::Config;
+use crate::markdown::TodoFile;
+use glob::Pattern;
+use std::path::{Component, Path, PathBuf};
+
+/// Returns every Markdown file under `root` that matches one of the include
+/// globs and none of the exclude globs, as paths relative to `root`.
+pub fn vault_files(
+    root: &Path,
+    include: &[String],
+    exclude: &[String],
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let excludes = exclude
+        .iter()
+        .map(|p| Pattern::new(p))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut files = Vec::new();
+
+    for pattern in include {
+        let full_pattern = root.join(pattern);
+        for entry in glob::glob(&full_pattern.to_string_lossy())? {
+            let path = entry?;
+            if !path.is_file() || path.extension().is_none_or(|ext| ext != "md") {
+                continue;
+            }
+
+            let relative = path.strip_prefix(root)?.to_path_buf();
+            if excludes.iter().any(|p| p.matches_path(&relative)) {
+                continue;
+            }
+
+            files.push(relative);
+        }
+    }
+
+    files.sort();
+    files.dedup();
+
+    Ok(files)
+}
+
+/// Every task file with the prefix its references take: each vault file as
+/// `path#` in vault mode, otherwise TODO.md with an empty prefix.
+pub fn todo_files(config: &Config) -> Result<Vec<(String, TodoFile)>, Box<dyn std::error::Error>> {
+    todo_paths(config)?
+        .into_iter()
+        .map(|(prefix, path)| Ok((prefix, TodoFile::load(&path)?)))
+        .collect()
+}
+
+/// The files behind `todo_files`, for commands that write them back.
+pub fn todo_paths(config: &Config) -> Result<Vec<(String, PathBuf)>, Box<dyn std::error::Error>> {
+    match config.vault_path()? {
+        Some(root) => Ok(vault_files(&root, &config.vault_include, &config.vault_exclude)?
+            .into_iter()
+            .map(|file| (format!("{}#", display_path(&file)), root.join(&file)))
+            .collect()),
+        None => Ok(vec![(String::new(), config.todo_path()?)]),
+    }
+}
+
+/// Splits a `path#Section:N` reference into the file it targets and the
+/// remaining `Section:N` part. References without `#` target `todo_path`.
+pub fn resolve_ref(
+    config: &Config,
+    reference: &str,
+) -> Result<(PathBuf, String), Box<dyn std::error::Error>> {
+    let (file, rest) = match reference.split_once('#') {
+        Some(parts) => parts,
+        None => return Ok((config.todo_path()?, reference.to_string())),
+    };
+
+    let root = config
+        .vault_path()?
+        .ok_or("Vault mode is not configured. Set vault_path to use path#Section references")?;
+
+    let relative = Path::new(file);
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(format!("Invalid vault path '{}'", file).into());
+    }
+
+    let path = root.join(relative);
+    if !path.is_file() {
+        return Err(format!("File '{}' not found in vault", file).into());
+    }
+
+    Ok((path, rest.to_string()))
+}
+
+/// Formats a vault-relative path the way it is written in references.
+pub fn display_path(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn vault_config(root: &Path) -> Config {
+        Config {
+            vault_path: Some(root.to_string_lossy().to_string()),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_vault_files_include_exclude() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("Projects")).unwrap();
+        fs::create_dir_all(root.join(".obsidian")).unwrap();
+        fs::create_dir_all(root.join("Templates")).unwrap();
+        fs::write(root.join("TODO.md"), "").unwrap();
+        fs::write(root.join("Projects/alpha.md"), "").unwrap();
+        fs::write(root.join("Projects/notes.txt"), "").unwrap();
+        fs::write(root.join(".obsidian/workspace.md"), "").unwrap();
+        fs::write(root.join("Templates/daily.md"), "").unwrap();
+
+        let mut exclude = Config::default().vault_exclude;
+        exclude.push("Templates/**".to_string());
+        let files = vault_files(root, &["**/*.md".to_string()], &exclude).unwrap();
+
+        assert_eq!(
+            files,
+            vec![PathBuf::from("Projects/alpha.md"), PathBuf::from("TODO.md")]
+        );
+    }
+
+    #[test]
+    fn test_resolve_ref() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("Projects")).unwrap();
+        fs::write(root.join("Projects/alpha.md"), "").unwrap();
+        let config = vault_config(root);
+
+        let (path, rest) = resolve_ref(&config, "Projects/alpha.md#Today:2").unwrap();
+        assert_eq!(path, root.join("Projects/alpha.md"));
+        assert_eq!(rest, "Today:2");
+
+        let (path, rest) = resolve_ref(&config, "Today:2").unwrap();
+        assert_eq!(path, PathBuf::from("./TODO.md"));
+        assert_eq!(rest, "Today:2");
+
+        assert!(resolve_ref(&config, "../outside.md#Today:1").is_err());
+        assert!(resolve_ref(&config, "missing.md#Today:1").is_err());
+    }
+
+    #[test]
+    fn test_resolve_ref_without_vault() {
+        let result = resolve_ref(&Config::default(), "notes.md#Today:1");
+        assert!(result.unwrap_err().to_string().contains("Vault mode is not configured"));
+    }
+}
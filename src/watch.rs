@@ -0,0 +1,264 @@
+use crate::markdown::TodoFile;
+use notify::{RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Editors often write a file in several steps; events closer together than
+/// this are handled as one change.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// A change between two parses of TODO.md. Tasks are matched by text, so an
+/// edit shows up as `task_edited` only when the old and new line sit at the
+/// same position in the same section.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Change {
+    SectionAdded { section: String },
+    SectionRemoved { section: String },
+    TaskAdded { section: String, text: String },
+    TaskRemoved { section: String, text: String },
+    TaskCompleted { section: String, text: String },
+    TaskReopened { section: String, text: String },
+    TaskMoved { from: String, to: String, text: String },
+    TaskEdited { section: String, old_text: String, text: String },
+}
+
+struct Entry<'a> {
+    section: &'a str,
+    position: usize,
+    text: &'a str,
+    done: bool,
+}
+
+fn entries(todo: &TodoFile) -> Vec<Entry<'_>> {
+    todo.sections
+        .iter()
+        .flat_map(|section| {
+            section.tasks.iter().enumerate().map(move |(position, task)| Entry {
+                section: &section.name,
+                position,
+                text: &task.text,
+                done: task.done,
+            })
+        })
+        .collect()
+}
+
+pub fn diff(old: &TodoFile, new: &TodoFile) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    for section in &new.sections {
+        if !old.sections.iter().any(|s| s.name == section.name) {
+            changes.push(Change::SectionAdded {
+                section: section.name.clone(),
+            });
+        }
+    }
+    for section in &old.sections {
+        if !new.sections.iter().any(|s| s.name == section.name) {
+            changes.push(Change::SectionRemoved {
+                section: section.name.clone(),
+            });
+        }
+    }
+
+    let old_entries = entries(old);
+    let new_entries = entries(new);
+    let mut old_matched = vec![false; old_entries.len()];
+    let mut new_matched = vec![false; new_entries.len()];
+
+    // Same text: prefer a partner in the same section, then anywhere.
+    for same_section in [true, false] {
+        for (ni, n) in new_entries.iter().enumerate() {
+            if new_matched[ni] {
+                continue;
+            }
+            let partner = old_entries.iter().enumerate().position(|(oi, o)| {
+                !old_matched[oi] && o.text == n.text && (!same_section || o.section == n.section)
+            });
+            if let Some(oi) = partner {
+                old_matched[oi] = true;
+                new_matched[ni] = true;
+
+                let o = &old_entries[oi];
+                if o.section != n.section {
+                    changes.push(Change::TaskMoved {
+                        from: o.section.to_string(),
+                        to: n.section.to_string(),
+                        text: n.text.to_string(),
+                    });
+                }
+                if o.done != n.done {
+                    changes.push(done_change(n));
+                }
+            }
+        }
+    }
+
+    let mut old_left: HashMap<(&str, usize), &Entry> = old_entries
+        .iter()
+        .enumerate()
+        .filter(|(oi, _)| !old_matched[*oi])
+        .map(|(_, o)| ((o.section, o.position), o))
+        .collect();
+
+    for (ni, n) in new_entries.iter().enumerate() {
+        if new_matched[ni] {
+            continue;
+        }
+        match old_left.remove(&(n.section, n.position)) {
+            Some(o) => {
+                changes.push(Change::TaskEdited {
+                    section: n.section.to_string(),
+                    old_text: o.text.to_string(),
+                    text: n.text.to_string(),
+                });
+                if o.done != n.done {
+                    changes.push(done_change(n));
+                }
+            }
+            None => changes.push(Change::TaskAdded {
+                section: n.section.to_string(),
+                text: n.text.to_string(),
+            }),
+        }
+    }
+
+    let mut removed: Vec<&Entry> = old_left.into_values().collect();
+    removed.sort_by_key(|o| (o.section, o.position));
+    for o in removed {
+        changes.push(Change::TaskRemoved {
+            section: o.section.to_string(),
+            text: o.text.to_string(),
+        });
+    }
+
+    changes
+}
+
+fn done_change(entry: &Entry) -> Change {
+    let section = entry.section.to_string();
+    let text = entry.text.to_string();
+    if entry.done {
+        Change::TaskCompleted { section, text }
+    } else {
+        Change::TaskReopened { section, text }
+    }
+}
+
+/// Watches `path` and writes one JSON object per change to `out` until the
+/// watcher fails.
+pub fn watch(path: &Path, out: &mut dyn Write) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let file_name = path.file_name().ok_or("Invalid TODO path")?.to_owned();
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    // Watch the directory: editors commonly save by renaming a temp file
+    // over the original, which drops a watch on the file itself.
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+    let mut current = TodoFile::load(path)?;
+
+    loop {
+        let event = rx.recv()??;
+        if !event.paths.iter().any(|p| p.file_name() == Some(file_name.as_os_str())) {
+            continue;
+        }
+        while rx.recv_timeout(DEBOUNCE).is_ok() {}
+
+        let next = TodoFile::load(path)?;
+        for change in diff(&current, &next) {
+            writeln!(out, "{}", serde_json::to_string(&change)?)?;
+        }
+        out.flush()?;
+        current = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> TodoFile {
+        TodoFile::parse(content).unwrap()
+    }
+
+    #[test]
+    fn test_diff_detects_task_changes() {
+        let old = parse("## Today\n- [ ] A\n- [ ] B\n- [ ] C\n\n## Next\n- [ ] D\n");
+        let new = parse("## Today\n- [x] A ✅ 2026-10-17\n- [ ] C2\n\n## Next\n- [ ] D\n- [ ] B\n- [ ] E\n");
+
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                Change::TaskCompleted {
+                    section: "Today".into(),
+                    text: "A".into()
+                },
+                Change::TaskMoved {
+                    from: "Today".into(),
+                    to: "Next".into(),
+                    text: "B".into()
+                },
+                Change::TaskAdded {
+                    section: "Today".into(),
+                    text: "C2".into()
+                },
+                Change::TaskAdded {
+                    section: "Next".into(),
+                    text: "E".into()
+                },
+                Change::TaskRemoved {
+                    section: "Today".into(),
+                    text: "C".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_edit_and_sections() {
+        let old = parse("## Today\n- [ ] Draft\n\n## Old\n");
+        let new = parse("## Today\n- [ ] Draft v2\n\n## New\n");
+
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                Change::SectionAdded {
+                    section: "New".into()
+                },
+                Change::SectionRemoved {
+                    section: "Old".into()
+                },
+                Change::TaskEdited {
+                    section: "Today".into(),
+                    old_text: "Draft".into(),
+                    text: "Draft v2".into()
+                },
+            ]
+        );
+        assert!(diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_change_json() {
+        let change = Change::TaskCompleted {
+            section: "Today".into(),
+            text: "A".into(),
+        };
+        assert_eq!(
+            serde_json::to_string(&change).unwrap(),
+            r#"{"event":"task_completed","section":"Today","text":"A"}"#
+        );
+    }
+}
@@ -1,4 +1,10 @@
-use mdtodo::markdown::{Task, TodoFile};
+use mdtodo::commands;
+use mdtodo::config::{AutoArchive, BlockedDone, Config, DoneLayout};
+use mdtodo::done_log;
+use mdtodo::export::ExportFormat;
+use mdtodo::import::ImportFormat;
+use mdtodo::markdown::{Task, TodoFile, DEPENDS_ON, ID};
+use mdtodo::server;
 use std::fs;
 use std::path::PathBuf;
 use tempfile::TempDir;
@@ -513,3 +519,657 @@ fn test_numbered_tasks() {
     assert_eq!(today_tasks[2].0, 3);
     assert_eq!(today_tasks[2].1.text, "Task C");
 }
+
+#[test]
+fn test_vault_mutation_targets_referenced_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path();
+    fs::create_dir_all(vault.join("Projects")).unwrap();
+    fs::write(vault.join("Projects/alpha.md"), "# Alpha\n\n## Today\n- [ ] Alpha task\n").unwrap();
+    fs::write(vault.join("beta.md"), "# Beta\n\n## Today\n- [ ] Beta task\n").unwrap();
+
+    let config = Config {
+        todo_path: vault.join("TODO.md").to_string_lossy().to_string(),
+        vault_path: Some(vault.to_string_lossy().to_string()),
+        ..Config::default()
+    };
+
+    commands::done(&config, "Projects/alpha.md#Today:1".to_string(), false).unwrap();
+
+    let alpha = TodoFile::load(&vault.join("Projects/alpha.md")).unwrap();
+    assert!(alpha.sections[0].tasks[0].done);
+    let beta = fs::read_to_string(vault.join("beta.md")).unwrap();
+    assert_eq!(beta, "# Beta\n\n## Today\n- [ ] Beta task\n");
+}
+
+#[test]
+fn test_carry_moves_unfinished_daily_tasks() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config {
+        daily_path: format!("{}/Daily/{{date}}.md", temp_dir.path().display()),
+        ..temp_config(&temp_dir)
+    };
+    let today = config.today().unwrap();
+    let yesterday = today - chrono::Duration::days(1);
+    let yesterday_path = mdtodo::daily::note_path(&config, yesterday).unwrap();
+    fs::create_dir_all(yesterday_path.parent().unwrap()).unwrap();
+    fs::write(
+        &yesterday_path,
+        "# Yesterday\n\n## Tasks\n- [ ] Open task\n- [x] Finished ✅ 2026-10-16\n\nLong day.\n",
+    )
+    .unwrap();
+    let today_path = mdtodo::daily::note_path(&config, today).unwrap();
+    fs::create_dir_all(today_path.parent().unwrap()).unwrap();
+    fs::write(&today_path, "# Today\n\nMorning notes.\n\n## Tasks\n\n## Journal\n  indented line\n").unwrap();
+
+    commands::carry(&config).unwrap();
+    assert_eq!(
+        fs::read_to_string(&yesterday_path).unwrap(),
+        "# Yesterday\n\n## Tasks\n- [x] Finished ✅ 2026-10-16\n\nLong day.\n"
+    );
+    assert_eq!(
+        fs::read_to_string(&today_path).unwrap(),
+        "# Today\n\nMorning notes.\n\n## Tasks\n- [ ] Open task\n\n## Journal\n  indented line\n"
+    );
+
+    let previous = TodoFile::load(&yesterday_path).unwrap();
+    assert_eq!(previous.sections[0].tasks.len(), 1);
+    assert!(previous.sections[0].tasks[0].done);
+
+    let note = TodoFile::load(&mdtodo::daily::note_path(&config, today).unwrap()).unwrap();
+    let tasks_idx = note.find_section("Tasks").unwrap();
+    assert_eq!(note.sections[tasks_idx].tasks.len(), 1);
+    assert_eq!(note.sections[tasks_idx].tasks[0].text, "Open task");
+}
+
+fn temp_config(temp_dir: &TempDir) -> Config {
+    let path = |name: &str| temp_dir.path().join(name).to_string_lossy().to_string();
+    Config {
+        todo_path: path("TODO.md"),
+        done_path: path("done_list.md"),
+        state_path: path("state.toml"),
+        ..Config::default()
+    }
+}
+
+#[test]
+fn test_archive_keeps_existing_done_log() {
+    let (temp_dir, todo_path, done_path) = setup_test_env();
+    let config = temp_config(&temp_dir);
+    fs::write(&todo_path, "# TODO\n\n## Today\n- [x] New ✅ 2026-02-14\n").unwrap();
+    fs::write(
+        &done_path,
+        "# Done Log\n\n## 2026-02-13\n\n### Today\n- [x] Old ✅ 2026-02-13\n",
+    )
+    .unwrap();
+
+    commands::archive(&config, "Today:all".to_string()).unwrap();
+
+    let done = fs::read_to_string(&done_path).unwrap();
+    assert!(done.contains("## 2026-02-14"));
+    assert!(done.contains("- [x] New ✅ 2026-02-14"));
+    assert!(done.contains("- [x] Old ✅ 2026-02-13"));
+    assert!(done.find("2026-02-14").unwrap() < done.find("2026-02-13").unwrap());
+}
+
+#[test]
+fn test_rollover_once_per_day() {
+    let (temp_dir, todo_path, done_path) = setup_test_env();
+    let mut config = temp_config(&temp_dir);
+    config.rollover.destination = Some("Next".to_string());
+    fs::write(
+        &todo_path,
+        "# TODO\n\n## Today\n- [ ] Open task\n- [x] Finished ✅ 2026-02-13\n\n## Next\n",
+    )
+    .unwrap();
+
+    commands::rollover(&config, false).unwrap();
+
+    let todo = TodoFile::load(&todo_path).unwrap();
+    assert!(todo.sections[0].tasks.is_empty());
+    assert_eq!(todo.sections[1].tasks[0].text, "Open task [carried:: 1]");
+    assert!(fs::read_to_string(&done_path).unwrap().contains("- [x] Finished ✅ 2026-02-13"));
+
+    // A second run on the same day is a no-op.
+    fs::write(&todo_path, "# TODO\n\n## Today\n- [ ] Another task\n").unwrap();
+    commands::rollover(&config, false).unwrap();
+    let todo = TodoFile::load(&todo_path).unwrap();
+    assert_eq!(todo.sections[0].tasks.len(), 1);
+}
+
+#[test]
+fn test_rollover_stamps_in_place() {
+    let (temp_dir, todo_path, _) = setup_test_env();
+    let config = temp_config(&temp_dir);
+    fs::write(&todo_path, "# TODO\n\n## Today\n- [ ] Task [carried:: 2]\n").unwrap();
+
+    commands::rollover(&config, false).unwrap();
+
+    let todo = TodoFile::load(&todo_path).unwrap();
+    assert_eq!(todo.sections[0].tasks[0].inline_field("carried"), Some("3".to_string()));
+}
+
+#[test]
+fn test_snooze_and_wake() {
+    let (temp_dir, todo_path, _) = setup_test_env();
+    let config = temp_config(&temp_dir);
+    fs::write(&todo_path, "# TODO\n\n## Today\n- [ ] Renew passport\n- [ ] Other\n").unwrap();
+
+    commands::snooze(&config, "Today:1".to_string(), "tomorrow".to_string(), None).unwrap();
+
+    let todo = TodoFile::load(&todo_path).unwrap();
+    let someday = todo.find_section("Someday").unwrap();
+    let task = &todo.sections[someday].tasks[0];
+    assert!(task.text.starts_with("Renew passport ⏳ "));
+    assert_eq!(task.inline_field("snoozed_from"), Some("Today".to_string()));
+
+    // Not due yet
+    assert_eq!(commands::wake_due(&config).unwrap(), 0);
+
+    commands::snooze(&config, "Someday:1".to_string(), "today".to_string(), None).unwrap();
+    assert_eq!(commands::wake_due(&config).unwrap(), 1);
+
+    let todo = TodoFile::load(&todo_path).unwrap();
+    let today = todo.find_section("Today").unwrap();
+    assert_eq!(todo.sections[today].tasks.len(), 2);
+    let woken = &todo.sections[today].tasks[1];
+    assert!(woken.text.starts_with("Renew passport ⏳ "));
+    assert_eq!(woken.inline_field("snoozed_from"), None);
+
+    assert!(commands::snooze(&config, "Today:0".to_string(), "tomorrow".to_string(), None).is_err());
+}
+
+#[test]
+fn test_wake_covers_vault_notes() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path();
+    fs::write(vault.join("TODO.md"), "# TODO\n\n## Today\n").unwrap();
+    fs::write(vault.join("trip.md"), "# Trip\n\n## Plan\n- [ ] Book hotel\n").unwrap();
+    let config = Config {
+        todo_path: vault.join("TODO.md").to_string_lossy().to_string(),
+        vault_path: Some(vault.to_string_lossy().to_string()),
+        ..Config::default()
+    };
+
+    commands::snooze(&config, "trip.md#Plan:1".to_string(), "today".to_string(), None).unwrap();
+    assert_eq!(commands::wake_due(&config).unwrap(), 1);
+
+    let trip = TodoFile::load(&vault.join("trip.md")).unwrap();
+    let plan = trip.find_section("Plan").unwrap();
+    assert_eq!(trip.sections[plan].tasks.len(), 1);
+    assert_eq!(trip.sections[plan].tasks[0].inline_field("snoozed_from"), None);
+    assert_eq!(fs::read_to_string(vault.join("TODO.md")).unwrap(), "# TODO\n\n## Today\n");
+}
+
+#[test]
+fn test_wait_moves_and_annotates_task() {
+    let (temp_dir, todo_path, _) = setup_test_env();
+    let config = temp_config(&temp_dir);
+    fs::write(&todo_path, "# TODO\n\n## Next\n- [ ] Contract signature\n\n## Waiting\n").unwrap();
+
+    commands::wait(
+        &config,
+        "Next:1".to_string(),
+        "Alice".to_string(),
+        Some("2026-11-01".to_string()),
+    )
+    .unwrap();
+
+    let todo = TodoFile::load(&todo_path).unwrap();
+    assert!(todo.sections[0].tasks.is_empty());
+    let task = &todo.sections[1].tasks[0];
+    assert_eq!(task.inline_field("waiting_on"), Some("Alice".to_string()));
+    assert_eq!(task.inline_field("waiting_since"), Some(config.today_str().unwrap()));
+    assert_eq!(task.inline_field("follow_up"), Some("2026-11-01".to_string()));
+
+    commands::waiting(&config).unwrap();
+    assert!(commands::wait(&config, "Waiting:0".to_string(), "Bob".to_string(), None).is_err());
+}
+
+#[test]
+fn test_waiting_covers_vault_notes() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path();
+    fs::write(vault.join("TODO.md"), "# TODO\n\n## Today\n").unwrap();
+    fs::write(vault.join("deal.md"), "# Deal\n\n## Next\n- [ ] Signed contract\n").unwrap();
+    let config = Config {
+        todo_path: vault.join("TODO.md").to_string_lossy().to_string(),
+        vault_path: Some(vault.to_string_lossy().to_string()),
+        ..Config::default()
+    };
+
+    commands::wait(&config, "deal.md#Next:1".to_string(), "Carol".to_string(), None).unwrap();
+
+    let deal = TodoFile::load(&vault.join("deal.md")).unwrap();
+    let waiting = deal.find_section("Waiting").unwrap();
+    assert_eq!(deal.sections[waiting].tasks[0].inline_field("waiting_on"), Some("Carol".to_string()));
+    commands::waiting(&config).unwrap();
+}
+
+#[test]
+fn test_review_applies_answers_and_records_date() {
+    let (temp_dir, todo_path, _) = setup_test_env();
+    let config = temp_config(&temp_dir);
+    fs::write(
+        &todo_path,
+        "# TODO\n\n## Inbox\n- [ ] Keep me ➕ 2020-01-01\n- [ ] Fresh ➕ 2999-01-01\n- [ ] Move me\n- [ ] Delete me\n\n## Next\n",
+    )
+    .unwrap();
+
+    let mut answers = std::io::Cursor::new("k\nm Next\nx\n");
+    commands::review_with(&config, &mut answers).unwrap();
+
+    let todo = TodoFile::load(&todo_path).unwrap();
+    let inbox = &todo.sections[todo.find_section("Inbox").unwrap()];
+    assert_eq!(inbox.tasks.len(), 2);
+    let today = config.today_str().unwrap();
+    assert_eq!(inbox.tasks[0].inline_field("reviewed"), Some(today.clone()));
+    assert_eq!(inbox.tasks[1].text, "Fresh ➕ 2999-01-01");
+
+    let next = &todo.sections[todo.find_section("Next").unwrap()];
+    assert!(next.tasks[0].text.starts_with("Move me"));
+
+    let state = mdtodo::state::State::load(&temp_dir.path().join("state.toml")).unwrap();
+    assert_eq!(state.last_review, Some(today));
+}
+
+#[test]
+fn test_capture_from_pasted_text() {
+    let (temp_dir, todo_path, _) = setup_test_env();
+    let config = temp_config(&temp_dir);
+    fs::write(&todo_path, "# TODO\n\n## Inbox\n").unwrap();
+
+    let mut input = std::io::Cursor::new(
+        "## Pasted\n- [ ] Checklist item\n* Bullet item\n1. Numbered item\n\nPlain line\n- [x] Already done\n#work call Bob\n#\n",
+    );
+    commands::capture_from(&config, &mut input, None).unwrap();
+
+    let todo = TodoFile::load(&todo_path).unwrap();
+    let texts: Vec<&str> = todo.sections[0].tasks.iter().map(|t| t.text.as_str()).collect();
+    assert_eq!(
+        texts,
+        vec!["Checklist item", "Bullet item", "Numbered item", "Plain line", "Already done", "#work call Bob"]
+    );
+    assert!(todo.sections[0].tasks[4].done);
+}
+
+#[test]
+fn test_capture_drop_dir_ingests_and_removes_files() {
+    let (temp_dir, todo_path, _) = setup_test_env();
+    let drop_dir = temp_dir.path().join("drop");
+    let archive_dir = temp_dir.path().join("drop-archive");
+    fs::create_dir_all(&drop_dir).unwrap();
+    fs::write(drop_dir.join("a.txt"), "Call Bob\n").unwrap();
+    fs::write(drop_dir.join("b.md"), "- [ ] Review draft\n").unwrap();
+    fs::write(drop_dir.join("c.json"), "{}").unwrap();
+    fs::write(drop_dir.join(".partial.txt"), "Not yet").unwrap();
+
+    let config = Config {
+        drop_archive_dir: Some(archive_dir.to_string_lossy().to_string()),
+        ..temp_config(&temp_dir)
+    };
+    commands::capture_drop_dir(&config, Some(drop_dir.to_string_lossy().to_string()), None)
+        .unwrap();
+
+    let todo = TodoFile::load(&todo_path).unwrap();
+    let inbox = &todo.sections[todo.find_section("Inbox").unwrap()];
+    assert_eq!(inbox.tasks.len(), 2);
+    assert_eq!(inbox.tasks[0].text, "Call Bob");
+    assert_eq!(inbox.tasks[1].text, "Review draft");
+
+    assert!(!drop_dir.join("a.txt").exists());
+    assert!(archive_dir.join("b.md").exists());
+    assert!(drop_dir.join("c.json").exists());
+    assert!(drop_dir.join(".partial.txt").exists());
+}
+
+#[test]
+fn test_unarchive_restores_to_original_section() {
+    let (temp_dir, todo_path, done_path) = setup_test_env();
+    fs::write(&todo_path, "# TODO\n\n## Today\n- [ ] Buy milk\n").unwrap();
+    fs::write(
+        &done_path,
+        "# Done Log\n\n## 2026-10-17\n\n### Next\n- [x] Review contract ✅ 2026-10-17\n- [x] Send invoice ✅ 2026-10-17\n",
+    )
+    .unwrap();
+    let config = temp_config(&temp_dir);
+
+    commands::unarchive(&config, "2026-10-17/Next:2".to_string(), true).unwrap();
+
+    let todo = TodoFile::load(&todo_path).unwrap();
+    let next = &todo.sections[todo.find_section("Next").unwrap()];
+    let restored = next.tasks.last().unwrap();
+    assert_eq!(restored.text, "Send invoice");
+    assert!(!restored.done);
+    assert_eq!(restored.done_date, None);
+
+    let log = fs::read_to_string(&done_path).unwrap();
+    assert!(log.contains("Review contract"));
+    assert!(!log.contains("Send invoice"));
+
+    commands::unarchive(&config, "2026-10-17/Next:1".to_string(), false).unwrap();
+    let todo = TodoFile::load(&todo_path).unwrap();
+    let next = &todo.sections[todo.find_section("Next").unwrap()];
+    assert!(next.tasks.last().unwrap().done);
+    assert!(!fs::read_to_string(&done_path).unwrap().contains("2026-10-17"));
+
+    assert!(commands::unarchive(&config, "2026-10-17/Next:1".to_string(), false).is_err());
+
+    // An age-based sweep keeps the task that was just restored as done.
+    let cutoff = config.today().unwrap();
+    assert_eq!(commands::archive_completed(&config, Some(cutoff)).unwrap(), 0);
+    assert_eq!(commands::archive_completed(&config, None).unwrap(), 1);
+    let log = fs::read_to_string(&done_path).unwrap();
+    assert!(log.contains("- [x] Review contract ✅ 2026-10-17\n"));
+}
+
+#[test]
+fn test_monthly_layout_and_compact() {
+    let (temp_dir, todo_path, done_path) = setup_test_env();
+    fs::write(
+        &todo_path,
+        "# TODO\n\n## Today\n- [x] October task ✅ 2026-10-17\n- [x] September task ✅ 2026-09-30\n",
+    )
+    .unwrap();
+    fs::write(
+        &done_path,
+        "# Done Log\n\n## 2026-08-01\n\n### Next\n- [x] Old task ✅ 2026-08-01\n",
+    )
+    .unwrap();
+    let done_dir = temp_dir.path().join("done");
+    let config = Config {
+        done_layout: DoneLayout::Monthly,
+        done_dir: done_dir.to_string_lossy().to_string(),
+        ..temp_config(&temp_dir)
+    };
+
+    commands::archive(&config, "Today:all".to_string()).unwrap();
+    assert!(fs::read_to_string(done_dir.join("2026-10.md")).unwrap().contains("October task"));
+    assert!(fs::read_to_string(done_dir.join("2026-09.md")).unwrap().contains("September task"));
+    assert_eq!(done_log::load_all(&config).unwrap().entries().len(), 3);
+
+    commands::compact_archive(&config).unwrap();
+    assert!(!done_path.exists());
+    assert!(fs::read_to_string(done_dir.join("2026-08.md")).unwrap().contains("Old task"));
+
+    let daily_config = Config {
+        done_layout: DoneLayout::Daily,
+        daily_path: format!("{}/Daily/{{date}}.md", temp_dir.path().display()),
+        ..config
+    };
+    commands::compact_archive(&daily_config).unwrap();
+    assert!(!done_dir.join("2026-10.md").exists());
+    let note = fs::read_to_string(temp_dir.path().join("Daily/2026-10-17.md")).unwrap();
+    assert!(note.contains("## Done\n\n### Today\n- [x] October task ✅ 2026-10-17"));
+    assert_eq!(done_log::load_all(&daily_config).unwrap().entries().len(), 3);
+
+    commands::unarchive(&daily_config, "2026-10-17/Today:1".to_string(), false).unwrap();
+    let note = fs::read_to_string(temp_dir.path().join("Daily/2026-10-17.md")).unwrap();
+    assert!(!note.contains("October task"));
+}
+
+#[test]
+fn test_archive_older_than_across_sections() {
+    let (temp_dir, todo_path, done_path) = setup_test_env();
+    fs::write(
+        &todo_path,
+        "# TODO\n\n## Today\n- [x] Old today ✅ 2020-01-01\n- [x] Recent\n- [ ] Open\n\n## Next\n- [x] Old next ✅ 2020-01-02\n",
+    )
+    .unwrap();
+    let config = temp_config(&temp_dir);
+
+    commands::archive_all(&config, Some("7d".to_string())).unwrap();
+
+    let todo = TodoFile::load(&todo_path).unwrap();
+    let texts: Vec<&str> = todo
+        .sections
+        .iter()
+        .flat_map(|s| s.tasks.iter().map(|t| t.text.as_str()))
+        .collect();
+    assert_eq!(texts, vec!["Recent", "Open"]);
+
+    let log = fs::read_to_string(&done_path).unwrap();
+    assert!(log.contains("## 2020-01-02\n\n### Next\n- [x] Old next"));
+    assert!(log.contains("## 2020-01-01\n\n### Today\n- [x] Old today"));
+
+    commands::archive_all(&config, None).unwrap();
+    let todo = TodoFile::load(&todo_path).unwrap();
+    assert_eq!(todo.sections[0].tasks.len(), 1);
+    assert!(fs::read_to_string(&done_path).unwrap().contains("## unknown"));
+}
+
+#[test]
+fn test_auto_archive_on_done() {
+    let (temp_dir, todo_path, done_path) = setup_test_env();
+    fs::write(&todo_path, "# TODO\n\n## Today\n- [ ] Ship it\n- [ ] Stay\n").unwrap();
+    let config = Config {
+        auto_archive: AutoArchive::OnDone,
+        ..temp_config(&temp_dir)
+    };
+
+    commands::done(&config, "Today:1".to_string(), false).unwrap();
+
+    let todo = TodoFile::load(&todo_path).unwrap();
+    assert_eq!(todo.sections[0].tasks.len(), 1);
+    assert_eq!(todo.sections[0].tasks[0].text, "Stay");
+    assert!(fs::read_to_string(&done_path).unwrap().contains("- [x] Ship it ✅"));
+}
+
+#[test]
+fn test_depend_and_blocked_done() {
+    let (temp_dir, todo_path, _) = setup_test_env();
+    fs::write(&todo_path, "# TODO\n\n## Today\n- [ ] Implement\n\n## Next\n- [ ] Write spec\n").unwrap();
+    let config = Config {
+        blocked_done: BlockedDone::Refuse,
+        ..temp_config(&temp_dir)
+    };
+
+    commands::depend(&config, "Today:1".to_string(), "Next:1".to_string()).unwrap();
+
+    let todo = TodoFile::load(&todo_path).unwrap();
+    let spec = &todo.sections[1].tasks[0];
+    let id = spec.field(ID).unwrap();
+    assert_eq!(todo.sections[0].tasks[0].field(DEPENDS_ON), Some(id.clone()));
+
+    // Depending again reuses the id and does not duplicate it
+    commands::depend(&config, "Today:1".to_string(), "Next:1".to_string()).unwrap();
+    let todo = TodoFile::load(&todo_path).unwrap();
+    assert_eq!(todo.sections[0].tasks[0].text, format!("Implement ⛔ {}", id));
+    assert!(commands::depend(&config, "Today:1".to_string(), "Today:1".to_string()).is_err());
+
+    assert!(commands::done(&config, "Today:1".to_string(), false).is_err());
+    commands::done(&config, "Next:1".to_string(), false).unwrap();
+    commands::done(&config, "Today:1".to_string(), false).unwrap();
+
+    fs::write(&todo_path, "# TODO\n\n## Today\n- [ ] Blocked ⛔ abc123\n- [ ] Blocker 🆔 abc123\n").unwrap();
+    commands::done(&config, "Today:1".to_string(), true).unwrap();
+    let warn_config = temp_config(&temp_dir);
+    fs::write(&todo_path, "# TODO\n\n## Today\n- [ ] Blocked ⛔ abc123\n- [ ] Blocker 🆔 abc123\n").unwrap();
+    commands::done(&warn_config, "Today:1".to_string(), false).unwrap();
+    assert!(TodoFile::load(&todo_path).unwrap().sections[0].tasks[0].done);
+}
+
+#[test]
+fn test_todotxt_import_and_export() {
+    let (temp_dir, todo_path, _) = setup_test_env();
+    fs::write(&todo_path, "# TODO\n\n## Today\n- [ ] Existing\n").unwrap();
+    let todotxt_path = temp_dir.path().join("todo.txt");
+    fs::write(
+        &todotxt_path,
+        "(A) Call Bob @phone +Today due:2026-10-20\nx 2026-10-17 Buy milk\nExisting +Today\n",
+    )
+    .unwrap();
+    let config = temp_config(&temp_dir);
+
+    commands::import(&config, ImportFormat::Todotxt, todotxt_path.to_string_lossy().to_string(), None, false)
+        .unwrap();
+
+    let todo = TodoFile::load(&todo_path).unwrap();
+    let today = &todo.sections[todo.find_section("Today").unwrap()];
+    assert_eq!(today.tasks.len(), 2);
+    assert_eq!(today.tasks[1].text, "Call Bob #phone 🔺 📅 2026-10-20");
+    let inbox = &todo.sections[todo.find_section("Inbox").unwrap()];
+    assert!(inbox.tasks[0].done);
+
+    let out = temp_dir.path().join("out.txt");
+    commands::export(&config, ExportFormat::Todotxt, Some(out.to_string_lossy().to_string())).unwrap();
+    assert_eq!(
+        fs::read_to_string(&out).unwrap(),
+        "Existing +Today\n(A) Call Bob @phone +Today due:2026-10-20\nx 2026-10-17 Buy milk +Inbox\n"
+    );
+}
+
+#[test]
+fn test_csv_export_and_replace_import() {
+    let (temp_dir, todo_path, _) = setup_test_env();
+    let original = "# TODO\n\n## Today\n- [ ] Pay rent 🔼 📅 2026-11-01\n- [x] Ship release 🆔 rel001 ✅ 2026-10-14\n\n## Next\n- [ ] Write notes ⛔ rel001\n";
+    fs::write(&todo_path, original).unwrap();
+    let config = temp_config(&temp_dir);
+
+    let csv_path = temp_dir.path().join("tasks.csv");
+    commands::export(&config, ExportFormat::Csv, Some(csv_path.to_string_lossy().to_string())).unwrap();
+    let csv = fs::read_to_string(&csv_path).unwrap();
+    assert!(csv.contains("Today,1,open,Pay rent,medium,,,,2026-11-01,,,,\n"));
+
+    // Edit the sheet: move a due date and swap the order within Today.
+    let edited = csv
+        .replace("Today,1,open,Pay rent,medium,,,,2026-11-01", "Today,2,open,Pay rent,medium,,,,2026-11-05")
+        .replace("Today,2,done,Ship release", "Today,1,done,Ship release");
+    fs::write(&csv_path, edited).unwrap();
+    fs::write(&todo_path, "# TODO\n\n## Today\n- [ ] Stale task\n").unwrap();
+
+    commands::import(&config, ImportFormat::Csv, csv_path.to_string_lossy().to_string(), None, true).unwrap();
+    assert_eq!(
+        fs::read_to_string(&todo_path).unwrap(),
+        "# TODO\n\n## Today\n- [x] Ship release 🆔 rel001 ✅ 2026-10-14\n- [ ] Pay rent 🔼 📅 2026-11-05\n\n## Next\n- [ ] Write notes ⛔ rel001\n\n"
+    );
+
+    let json_path = temp_dir.path().join("tasks.json");
+    commands::export(&config, ExportFormat::Json, Some(json_path.to_string_lossy().to_string())).unwrap();
+    commands::import(&config, ImportFormat::Json, json_path.to_string_lossy().to_string(), None, false).unwrap();
+    let todo = TodoFile::load(&todo_path).unwrap();
+    assert_eq!(todo.sections[todo.find_section("Today").unwrap()].tasks.len(), 2);
+}
+
+#[test]
+fn test_csv_merge_round_trip_keeps_duplicate_texts() {
+    let (temp_dir, todo_path, _) = setup_test_env();
+    let original = "# TODO\n\n## Today\n- [ ] Call mom\n- [ ] Pay rent\n\n## Next\n- [ ] Call mom 📅 2026-11-01\n- [ ] Call mom\n";
+    fs::write(&todo_path, original).unwrap();
+    let config = temp_config(&temp_dir);
+
+    let csv_path = temp_dir.path().join("tasks.csv");
+    commands::export(&config, ExportFormat::Csv, Some(csv_path.to_string_lossy().to_string())).unwrap();
+    commands::import(&config, ImportFormat::Csv, csv_path.to_string_lossy().to_string(), None, false).unwrap();
+    assert_eq!(fs::read_to_string(&todo_path).unwrap(), original);
+
+    // Without positions, repeated rows still update one task each.
+    let rows = "section,status,text\nNext,open,Call mom\nNext,done,Call mom\n";
+    fs::write(&csv_path, rows).unwrap();
+    commands::import(&config, ImportFormat::Csv, csv_path.to_string_lossy().to_string(), None, false).unwrap();
+    let todo = TodoFile::load(&todo_path).unwrap();
+    let next = &todo.sections[todo.find_section("Next").unwrap()];
+    assert_eq!(next.tasks.len(), 2);
+    assert!(next.tasks[1].done);
+    assert_eq!(todo.sections[todo.find_section("Today").unwrap()].tasks.len(), 2);
+}
+
+/// Sends one HTTP/1.1 request and returns the status and JSON body.
+fn http(addr: std::net::SocketAddr, method: &str, path: &str, body: &str) -> (u16, serde_json::Value) {
+    let host = format!("localhost:{}", addr.port());
+    http_with(addr, method, path, &host, "application/json", body)
+}
+
+fn http_with(
+    addr: std::net::SocketAddr,
+    method: &str,
+    path: &str,
+    host: &str,
+    content_type: &str,
+    body: &str,
+) -> (u16, serde_json::Value) {
+    use std::io::{Read, Write};
+    let mut stream = std::net::TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method,
+        path,
+        host,
+        content_type,
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap_or(serde_json::Value::Null))
+}
+
+#[test]
+fn test_serve_rest_api() {
+    let (temp_dir, todo_path, _) = setup_test_env();
+    fs::write(&todo_path, "# TODO\n\n## Today\n- [ ] Write report\n- [ ] Call Bob\n\n## Next Week\n").unwrap();
+    let config = temp_config(&temp_dir);
+
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let addr = server.server_addr().to_ip().unwrap();
+    let server_config = config.clone();
+    std::thread::spawn(move || server::run(&server, &server_config, None));
+
+    let (status, sections) = http(addr, "GET", "/sections", "");
+    assert_eq!(status, 200);
+    assert_eq!(sections[0]["name"], "Today");
+    assert_eq!(sections[0]["tasks"][1]["text"], "Call Bob");
+
+    let (status, task) = http(addr, "POST", "/tasks", r#"{"section": "Next Week", "text": "Plan sprint"}"#);
+    assert_eq!((status, task["number"].as_u64()), (201, Some(1)));
+
+    let (status, task) = http(addr, "PATCH", "/tasks/today/1", r#"{"done": true}"#);
+    assert_eq!(status, 200);
+    assert_eq!(task["done_date"], config.today_str().unwrap());
+
+    let (status, task) = http(addr, "PATCH", "/tasks/Today/2", r#"{"text": "Call Bob back", "section": "Next Week"}"#);
+    assert_eq!(status, 200);
+    assert_eq!((task["section"].as_str(), task["number"].as_u64()), (Some("Next Week"), Some(2)));
+
+    let (status, tasks) = http(addr, "GET", "/tasks?section=Next%20Week", "");
+    assert_eq!(status, 200);
+    assert_eq!(tasks.as_array().unwrap().len(), 2);
+
+    let (status, task) = http(addr, "DELETE", "/tasks/Next%20Week/1", "");
+    assert_eq!((status, task["text"].as_str()), (200, Some("Plan sprint")));
+
+    assert_eq!(http(addr, "GET", "/tasks/Today/9", "").0, 404);
+    assert_eq!(http(addr, "POST", "/tasks", "not json").0, 400);
+    assert_eq!(http(addr, "PUT", "/tasks", "").0, 405);
+
+    // A cross-site form post and a DNS-rebound page are both turned away.
+    let host = format!("localhost:{}", addr.port());
+    let form = http_with(addr, "POST", "/tasks", &host, "text/plain", r#"{"text": "Injected"}"#);
+    assert_eq!(form.0, 415);
+    let rebound = http_with(addr, "GET", "/tasks", &format!("evil.example:{}", addr.port()), "application/json", "");
+    assert_eq!(rebound.0, 403);
+    assert!(!fs::read_to_string(&todo_path).unwrap().contains("Injected"));
+
+    let content = fs::read_to_string(&todo_path).unwrap();
+    assert!(content.contains(&format!("## Today\n- [x] Write report ✅ {}\n", config.today_str().unwrap())));
+    assert!(content.contains("## Next Week\n- [ ] Call Bob back\n"));
+    assert!(!content.contains("Plan sprint"));
+}
+
+#[test]
+fn test_only_writes_take_the_lock() {
+    let (temp_dir, todo_path, _) = setup_test_env();
+    let config = temp_config(&temp_dir);
+    let lock_path = temp_dir.path().join(".TODO.md.lock");
+
+    commands::list(&config, None, false).unwrap();
+    assert!(!lock_path.exists());
+
+    commands::add(&config, "Today".to_string(), "Locked write".to_string()).unwrap();
+    assert!(lock_path.exists());
+    assert!(fs::read_to_string(&todo_path).unwrap().contains("Locked write"));
+}